@@ -26,11 +26,54 @@ macro_rules! count {
 /// # }
 /// ```
 ///
+/// The capacity and the hasher of the map can also be given before the pairs:
+///
+/// ```rust
+/// use std::collections::hash_map::RandomState;
+/// use sugars::hmap;
+///
+/// # fn main() {
+/// let map = hmap! {cap: 128; "a" => 1};
+/// assert!(map.capacity() >= 128);
+///
+/// let map = hmap! {hasher: RandomState::new(); "a" => 1};
+/// let map = hmap! {cap: 128, hasher: RandomState::new(); "a" => 1};
+/// # }
+/// ```
+///
 /// [`HashMap`]: std::collections::HashMap
 #[macro_export]
 macro_rules! hmap {
     () => { ::std::collections::HashMap::new() };
 
+    (cap: $cap: expr, hasher: $hasher: expr $(; $($key: expr => $value: expr),* $(,)?)?) => {{
+        #[allow(unused_mut)]
+        let mut map = ::std::collections::HashMap::with_capacity_and_hasher($cap, $hasher);
+        $($(
+            let _ = map.insert($key, $value);
+        )*)?
+        map
+    }};
+
+    (cap: $cap: expr $(; $($key: expr => $value: expr),* $(,)?)?) => {{
+        #[allow(unused_mut)]
+        let mut map = ::std::collections::HashMap::with_capacity($cap);
+        $($(
+            let _ = map.insert($key, $value);
+        )*)?
+        map
+    }};
+
+    (hasher: $hasher: expr $(; $($key: expr => $value: expr),* $(,)?)?) => {{
+        const CAP: usize = $crate::count!($($($key),*)?);
+        #[allow(unused_mut)]
+        let mut map = ::std::collections::HashMap::with_capacity_and_hasher(CAP, $hasher);
+        $($(
+            let _ = map.insert($key, $value);
+        )*)?
+        map
+    }};
+
     ( $($key: expr => $value: expr),+ $(,)? ) => {{
             const CAP: usize = $crate::count!($($key),*);
             let mut map = ::std::collections::HashMap::with_capacity(CAP);
@@ -57,11 +100,54 @@ macro_rules! hmap {
 /// # }
 /// ```
 ///
-/// [`HashSet`]: std::collections::HashMap
+/// The capacity and the hasher of the set can also be given before the elements:
+///
+/// ```rust
+/// use std::collections::hash_map::RandomState;
+/// use sugars::hset;
+///
+/// # fn main() {
+/// let set = hset! {cap: 64; "a", "b"};
+/// assert!(set.capacity() >= 64);
+///
+/// let set = hset! {hasher: RandomState::new(); "a", "b"};
+/// let set = hset! {cap: 64, hasher: RandomState::new(); "a", "b"};
+/// # }
+/// ```
+///
+/// [`HashSet`]: std::collections::HashSet
 #[macro_export]
 macro_rules! hset {
     () => { ::std::collections::HashSet::new() };
 
+    (cap: $cap: expr, hasher: $hasher: expr $(; $($elem: expr),* $(,)?)?) => {{
+        #[allow(unused_mut)]
+        let mut set = ::std::collections::HashSet::with_capacity_and_hasher($cap, $hasher);
+        $($(
+            let _ = set.insert($elem);
+        )*)?
+        set
+    }};
+
+    (cap: $cap: expr $(; $($elem: expr),* $(,)?)?) => {{
+        #[allow(unused_mut)]
+        let mut set = ::std::collections::HashSet::with_capacity($cap);
+        $($(
+            let _ = set.insert($elem);
+        )*)?
+        set
+    }};
+
+    (hasher: $hasher: expr $(; $($elem: expr),* $(,)?)?) => {{
+        const CAP: usize = $crate::count!($($($elem),*)?);
+        #[allow(unused_mut)]
+        let mut set = ::std::collections::HashSet::with_capacity_and_hasher(CAP, $hasher);
+        $($(
+            let _ = set.insert($elem);
+        )*)?
+        set
+    }};
+
     ($($elem: expr),+ $(,)?) => {{
         const CAP: usize = $crate::count!($($elem),*);
         let mut set = ::std::collections::HashSet::with_capacity(CAP);
@@ -299,6 +385,9 @@ macro_rules! rlkl {
 /// assert_eq!(Some(2), heap.pop());
 /// assert_eq!(Some(1), heap.pop());
 /// assert_eq!(None, heap.pop());
+///
+/// let zeros = bheap![0; 5];
+/// assert_eq!(5, zeros.len());
 /// # }
 /// ```
 ///
@@ -307,6 +396,14 @@ macro_rules! rlkl {
 macro_rules! bheap {
     () => { ::std::collections::BinaryHeap::new() };
 
+    ($elem: expr; $n: expr) => {{
+        let n = $n;
+        let elem = $elem;
+        let mut bheap = ::std::collections::BinaryHeap::with_capacity(n);
+        (0..n).for_each(|_| bheap.push(::std::clone::Clone::clone(&elem)));
+        bheap
+    }};
+
     ( $($elem: expr),+ $(,)? ) => {{
         const CAP: usize = $crate::count!($($elem),*);
         let mut bheap = ::std::collections::BinaryHeap::with_capacity(CAP);
@@ -318,6 +415,252 @@ macro_rules! bheap {
     }}
 }
 
+/// Create a min-heap [`BinaryHeap`] from a list of elements, wrapping each one in [`Reverse`].
+///
+/// The smallest element is popped first. Since the elements are wrapped, unwrap the popped
+/// values with `.0`.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::cmp::Reverse;
+/// use sugars::bheap_min;
+/// # fn main() {
+/// let mut heap = bheap_min![3, 1, 2];
+///
+/// assert_eq!(Some(Reverse(1)), heap.pop());
+/// assert_eq!(Some(2), heap.pop().map(|x| x.0));
+///
+/// let zeros = bheap_min![0; 5];
+/// assert_eq!(5, zeros.len());
+/// # }
+/// ```
+///
+/// [`BinaryHeap`]: std::collections::BinaryHeap
+/// [`Reverse`]: std::cmp::Reverse
+#[macro_export]
+macro_rules! bheap_min {
+    () => { ::std::collections::BinaryHeap::<::std::cmp::Reverse<_>>::new() };
+
+    ($elem: expr; $n: expr) => {
+        $crate::bheap![::std::cmp::Reverse($elem); $n]
+    };
+
+    ( $($elem: expr),+ $(,)? ) => {
+        $crate::bheap![$(::std::cmp::Reverse($elem)),+]
+    };
+}
+
+/// Create a [`HashMap`] from a list of keys, computing each value from its key.
+///
+/// The function receives a clone of each key, so the key type must implement [`Clone`].
+///
+/// # Example
+///
+/// ```rust
+/// use sugars::map_from_keys;
+///
+/// # fn main() {
+/// let map = map_from_keys!(|k| k * k; vec![1, 2, 3]);
+///
+/// assert_eq!(map[&1], 1);
+/// assert_eq!(map[&2], 4);
+/// assert_eq!(map[&3], 9);
+/// # }
+/// ```
+///
+/// [`HashMap`]: std::collections::HashMap
+#[macro_export]
+macro_rules! map_from_keys {
+    ($f: expr; $keys: expr) => {{
+        #[allow(unused_mut)]
+        let mut f = $f;
+        $keys
+            .into_iter()
+            .map(|k| {
+                let v = f(::std::clone::Clone::clone(&k));
+                (k, v)
+            })
+            .collect::<::std::collections::HashMap<_, _>>()
+    }};
+}
+
+/// Create a [`BTreeMap`] from a list of keys, computing each value from its key.
+///
+/// The function receives a clone of each key, so the key type must implement [`Clone`].
+///
+/// # Example
+///
+/// ```rust
+/// use sugars::btmap_from_keys;
+///
+/// # fn main() {
+/// let map = btmap_from_keys!(|k| k * 2; vec![1, 2, 3]);
+///
+/// let mut iter = map.iter();
+/// assert_eq!(Some((&1, &2)), iter.next());
+/// assert_eq!(Some((&2, &4)), iter.next());
+/// assert_eq!(Some((&3, &6)), iter.next());
+/// assert_eq!(None, iter.next());
+/// # }
+/// ```
+///
+/// [`BTreeMap`]: std::collections::BTreeMap
+#[macro_export]
+macro_rules! btmap_from_keys {
+    ($f: expr; $keys: expr) => {{
+        #[allow(unused_mut)]
+        let mut f = $f;
+        $keys
+            .into_iter()
+            .map(|k| {
+                let v = f(::std::clone::Clone::clone(&k));
+                (k, v)
+            })
+            .collect::<::std::collections::BTreeMap<_, _>>()
+    }};
+}
+
+/// Transpose a rectangular grid given as a [`Vec`] of rows, so its columns become rows.
+///
+/// The elements must implement [`Clone`].
+///
+/// # Panics
+/// Panics if the grid is ragged, i.e. not all rows have the same length.
+///
+/// # Example
+///
+/// ```rust
+/// use sugars::transpose;
+///
+/// # fn main() {
+/// let grid = vec![vec![1, 2, 3], vec![4, 5, 6]];
+///
+/// assert_eq!(vec![vec![1, 4], vec![2, 5], vec![3, 6]], transpose!(grid));
+/// # }
+/// ```
+#[macro_export]
+macro_rules! transpose {
+    ($grid: expr) => {{
+        let grid: ::std::vec::Vec<::std::vec::Vec<_>> = $grid;
+        let cols = grid.first().map_or(0, |row| row.len());
+        assert!(
+            grid.iter().all(|row| row.len() == cols),
+            "transpose!: all rows must have the same length"
+        );
+        (0..cols)
+            .map(|j| {
+                grid.iter()
+                    .map(|row| ::std::clone::Clone::clone(&row[j]))
+                    .collect()
+            })
+            .collect::<::std::vec::Vec<::std::vec::Vec<_>>>()
+    }};
+}
+
+/// Create a [`HashMap`] by swapping the keys and values of a map.
+///
+/// It accepts anything that iterates over key-value pairs, so the map is consumed. If more than
+/// one key share the same value, the last key in iteration order wins, which for a
+/// [`HashMap`] source is unspecified.
+///
+/// # Example
+///
+/// ```rust
+/// use sugars::{hmap, invert_map};
+///
+/// # fn main() {
+/// let map = hmap! {"a" => 1, "b" => 2};
+/// let inverted = invert_map!(map);
+///
+/// assert_eq!(inverted[&1], "a");
+/// assert_eq!(inverted[&2], "b");
+/// # }
+/// ```
+///
+/// [`HashMap`]: std::collections::HashMap
+#[macro_export]
+macro_rules! invert_map {
+    ($map: expr) => {
+        $map.into_iter()
+            .map(|(k, v)| (v, k))
+            .collect::<::std::collections::HashMap<_, _>>()
+    };
+}
+
+/// Create a [`BTreeMap`] by swapping the keys and values of a map.
+///
+/// It accepts anything that iterates over key-value pairs, so the map is consumed. If more than
+/// one key share the same value, the last key in iteration order wins.
+///
+/// # Example
+///
+/// ```rust
+/// use sugars::{btmap, invert_btmap};
+///
+/// # fn main() {
+/// let map = btmap! {"a" => 1, "b" => 1, "c" => 2};
+/// let inverted = invert_btmap!(map);
+///
+/// assert_eq!(inverted[&1], "b");
+/// assert_eq!(inverted[&2], "c");
+/// # }
+/// ```
+///
+/// [`BTreeMap`]: std::collections::BTreeMap
+#[macro_export]
+macro_rules! invert_btmap {
+    ($map: expr) => {
+        $map.into_iter()
+            .map(|(k, v)| (v, k))
+            .collect::<::std::collections::BTreeMap<_, _>>()
+    };
+}
+
+/// Create a [`HashMap`] counting how many times each item appears.
+///
+/// It accepts anything that implements [`IntoIterator`] over items implementing [`Eq`] and
+/// [`Hash`]. The `btree` form creates a [`BTreeMap`] instead, for items implementing [`Ord`].
+///
+/// # Example
+///
+/// ```rust
+/// use sugars::{btmap, counter};
+///
+/// # fn main() {
+/// let counts = counter!("aabbbc".chars());
+///
+/// assert_eq!(counts[&'a'], 2);
+/// assert_eq!(counts[&'b'], 3);
+/// assert_eq!(counts[&'c'], 1);
+///
+/// let sorted = counter!(btree vec![3, 1, 3]);
+/// assert_eq!(btmap! {1 => 1, 3 => 2}, sorted);
+/// # }
+/// ```
+///
+/// [`HashMap`]: std::collections::HashMap
+/// [`BTreeMap`]: std::collections::BTreeMap
+/// [`Hash`]: std::hash::Hash
+#[macro_export]
+macro_rules! counter {
+    (btree $iter: expr) => {{
+        let mut counts = ::std::collections::BTreeMap::<_, usize>::new();
+        for item in $iter {
+            *counts.entry(item).or_insert(0) += 1;
+        }
+        counts
+    }};
+
+    ($iter: expr) => {{
+        let mut counts = ::std::collections::HashMap::<_, usize>::new();
+        for item in $iter {
+            *counts.entry(item).or_insert(0) += 1;
+        }
+        counts
+    }};
+}
+
 #[cfg(test)]
 mod tests {
     use std::collections::*;
@@ -336,6 +679,36 @@ mod tests {
         assert!(map2.is_empty());
     }
 
+    #[test]
+    fn hmap_capacity() {
+        let map = hmap! {cap: 128; "a" => 1, "b" => 2};
+        assert!(map.capacity() >= 128);
+        assert_eq!(map["b"], 2);
+
+        let map: HashMap<i32, i32> = hmap! {cap: 64};
+        assert!(map.capacity() >= 64);
+        assert!(map.is_empty());
+    }
+
+    #[test]
+    fn hmap_hasher() {
+        use std::hash::BuildHasherDefault;
+
+        type Hasher = BuildHasherDefault<hash_map::DefaultHasher>;
+
+        let map: HashMap<_, _, Hasher> = hmap! {hasher: Hasher::default(); "a" => 1, "b" => 2,};
+        assert_eq!(map["a"], 1);
+        assert_eq!(map["b"], 2);
+        assert_eq!(map.get("c"), None);
+
+        let map: HashMap<&str, i32, Hasher> = hmap! {hasher: Hasher::default()};
+        assert!(map.is_empty());
+
+        let map: HashMap<_, _, Hasher> = hmap! {cap: 32, hasher: Hasher::default(); 1 => 'a'};
+        assert!(map.capacity() >= 32);
+        assert_eq!(map[&1], 'a');
+    }
+
     #[test]
     fn hset() {
         let set = hset! {"a", "b"};
@@ -361,6 +734,36 @@ mod tests {
         assert!(map2.is_empty());
     }
 
+    #[test]
+    fn hset_capacity() {
+        let set = hset! {cap: 64; 1, 2, 3};
+        assert!(set.capacity() >= 64);
+        assert!(set.contains(&2));
+
+        let set: HashSet<u8> = hset! {cap: 16};
+        assert!(set.capacity() >= 16);
+        assert!(set.is_empty());
+    }
+
+    #[test]
+    fn hset_hasher() {
+        use std::hash::BuildHasherDefault;
+
+        type Hasher = BuildHasherDefault<hash_map::DefaultHasher>;
+
+        let set: HashSet<_, Hasher> = hset! {hasher: Hasher::default(); 1, 2, 3,};
+        assert!(set.contains(&1));
+        assert!(set.contains(&3));
+        assert!(!set.contains(&4));
+
+        let set: HashSet<&str, Hasher> = hset! {hasher: Hasher::default()};
+        assert!(set.is_empty());
+
+        let set: HashSet<_, Hasher> = hset! {cap: 32, hasher: Hasher::default(); "a"};
+        assert!(set.capacity() >= 32);
+        assert!(set.contains("a"));
+    }
+
     #[test]
     fn btset() {
         let set = btset! {"a", "b"};
@@ -469,6 +872,125 @@ mod tests {
         }
     }
 
+    #[test]
+    fn bheap_repeat() {
+        let mut test = bheap![String::from("a"); 5];
+        assert_eq!(5, test.len());
+        while let Some(elem) = test.pop() {
+            assert_eq!("a", elem);
+        }
+
+        let test: BinaryHeap<i32> = bheap![7; 0];
+        assert!(test.is_empty());
+    }
+
+    #[test]
+    fn bheap_min() {
+        use std::cmp::Reverse;
+
+        let mut test = bheap_min![4, 1, 5, 3, 2,];
+        let mut popped = Vec::new();
+        while let Some(Reverse(elem)) = test.pop() {
+            popped.push(elem);
+        }
+        assert_eq!(vec![1, 2, 3, 4, 5], popped);
+
+        let mut test = bheap_min!["a"; 3];
+        assert_eq!(3, test.len());
+        assert_eq!(Some(Reverse("a")), test.pop());
+
+        let test: BinaryHeap<Reverse<i32>> = bheap_min![];
+        assert!(test.is_empty());
+    }
+
+    #[test]
+    fn map_from_keys() {
+        let keys = vec![1, 2, 3, 4];
+        let map = map_from_keys!(|k| k * k; keys.clone());
+
+        assert_eq!(keys.len(), map.len());
+        for k in keys {
+            assert_eq!(k * k, map[&k]);
+        }
+    }
+
+    #[test]
+    fn btmap_from_keys() {
+        let map = btmap_from_keys!(|k: &str| k.len(); vec!["a", "bb", "ccc"]);
+        let expected = btmap! {"a" => 1, "bb" => 2, "ccc" => 3};
+
+        assert_eq!(expected, map);
+    }
+
+    #[test]
+    fn transpose() {
+        let grid = vec![vec![1, 2, 3], vec![4, 5, 6]];
+        let expected = vec![vec![1, 4], vec![2, 5], vec![3, 6]];
+
+        assert_eq!(expected, transpose!(grid));
+        assert_eq!(Vec::<Vec<i32>>::new(), transpose!(Vec::<Vec<i32>>::new()));
+    }
+
+    #[test]
+    #[should_panic]
+    fn transpose_ragged() {
+        let _ = transpose!(vec![vec![1, 2, 3], vec![4, 5]]);
+    }
+
+    #[test]
+    fn invert_map() {
+        let map = hmap! {"a" => 1, "b" => 2, "c" => 3};
+        let inverted = invert_map!(map.clone());
+
+        assert_eq!(hmap! {1 => "a", 2 => "b", 3 => "c"}, inverted);
+        assert_eq!(map, invert_map!(inverted));
+    }
+
+    #[test]
+    fn invert_map_collision() {
+        let map = btmap! {"a" => 1, "b" => 2, "c" => 1};
+        let inverted = invert_map!(map);
+
+        assert_eq!(2, inverted.len());
+        assert_eq!("c", inverted[&1]);
+        assert_eq!("b", inverted[&2]);
+    }
+
+    #[test]
+    fn invert_btmap() {
+        let map = btmap! {"a" => 1, "b" => 2, "c" => 1};
+        let inverted = invert_btmap!(map);
+
+        assert_eq!(btmap! {1 => "c", 2 => "b"}, inverted);
+        assert_eq!(btmap! {"b" => 2, "c" => 1}, invert_btmap!(inverted));
+    }
+
+    #[test]
+    fn counter() {
+        let counts = counter!("aabbbc".chars());
+
+        assert_eq!(hmap! {'a' => 2, 'b' => 3, 'c' => 1}, counts);
+    }
+
+    #[test]
+    fn counter_empty() {
+        let counts = counter!(Vec::<i32>::new());
+
+        assert!(counts.is_empty());
+    }
+
+    #[test]
+    fn counter_btree() {
+        let words = ["b", "a", "b", "c", "b"];
+        let counts = counter!(btree words.iter());
+
+        assert_eq!(btmap! {&"a" => 1, &"b" => 3, &"c" => 1}, counts);
+        assert_eq!(
+            vec![&"a", &"b", &"c"],
+            counts.keys().copied().collect::<Vec<_>>()
+        );
+    }
+
     #[test]
     fn trailing_all() {
         hmap! {"a" => 1,};
@@ -4,8 +4,26 @@
 ///
 /// Nice to have when what you collecting are not in `std::collections` or not in the `std` at all.
 ///
-/// ## Limitations
-///  * Only 3 nested comprehensions
+/// Any number of generators can be nested, the first one being the outermost loop, and the
+/// optional `if` conditions at the end are checked at the innermost level. Each condition is
+/// chained as its own filter, so all of them must hold for an element to be kept.
+///
+/// Instead of the `if` conditions, a single `if let <pattern> = <expr>` guard can be used to
+/// keep only the elements matching the pattern. The variables bound by the pattern can be used
+/// in the output expression.
+///
+/// Any generator can be followed by `let <pattern> = <expr>` clauses, binding intermediate
+/// values once per element so they can be used by the next clauses and the output expression.
+///
+/// Writing `enumerate` before the iterator of a generator wraps it in [`Iterator::enumerate`],
+/// binding `(index, item)` pairs. The indices are 0-based and count the items of that generator
+/// before any condition is applied. For a nested generator, they restart at 0 for each element
+/// of the outer generators.
+///
+/// Writing `zip` before a generator pairs it element-wise with the previous generator, as
+/// [`Iterator::zip`] does, instead of nesting it. It stops at the end of the shorter of the two
+/// iterators. Both are treated as a single generator by the clauses that follow, so
+/// `a in xs, zip b in ys, c in zs` is the product of the zipped pairs with `zs`.
 ///
 /// # Examples:
 /// ```rust
@@ -13,42 +31,126 @@
 /// use sugars::c;
 /// let w: Vec<_> = c![x; x in 1..10].collect();
 /// let z: HashSet<_> = c!{x; x in 1..10, if x%2 == 0}.collect();
+/// let y: Vec<_> = c![x; x in 0..100, if x%2 == 0, if x%3 == 0].collect();
+/// let opts = vec![Some(1), None, Some(3)];
+/// let v: Vec<_> = c![v; item in opts.into_iter(), if let Some(v) = item].collect();
+/// let s: Vec<_> = c![y; x in 0..10, let y = x * x, if y % 2 == 0].collect();
+/// let n: Vec<_> = c![(a, b, c, d); a in 0..2, b in 0..2, c in 0..2, d in 0..2].collect();
+/// let e: Vec<_> = c![(i, x); (i, x) in enumerate "abc".chars()].collect();
+/// let p: Vec<_> = c![a * b; a in 1..4, zip b in 4..7].collect();
+/// assert_eq!(vec![4, 10, 18], p);
 /// ```
 #[macro_export]
 macro_rules! c {
-    ($e:expr; $i:pat in $iter:expr) => {
-        $iter.map(|$i| $e)
-    };
+    // `let` bindings, lowered to a block so later clauses and the output can use the binding.
+    (@nest $e:expr; let $p:pat = $x:expr) => {{
+        let $p = $x;
+        ::std::iter::once($e)
+    }};
 
-    ($e:expr; $i:pat in $iter:expr, if $cond:expr) => {{
-        $iter.filter(|$i| $cond).map(|$i| $e)
+    (@nest $e:expr; let $p:pat = $x:expr, if let $q:pat = $y:expr) => {{
+        let $p = $x;
+        match $y {
+            $q => Some($e),
+            #[allow(unreachable_patterns)]
+            _ => None,
+        }
+        .into_iter()
     }};
 
-    ($e:expr; $i1:pat in $iter1:expr, $i2:pat in $iter2:expr) => {{
-        $iter1.flat_map(|$i1| $iter2.map(move |$i2| $e))
+    (@nest $e:expr; let $p:pat = $x:expr, $(if $cond:expr),+) => {{
+        let $p = $x;
+        if $($cond)&&+ { Some($e) } else { None }.into_iter()
     }};
 
-    ($e:expr; $i1:pat in $iter1:expr, $i2:pat in $iter2:expr, if $cond:expr) => {{
-        $iter1.flat_map(|$i1| $iter2.filter_map(move |$i2| if $cond { Some($e) } else { None }))
+    (@nest $e:expr; let $p:pat = $x:expr, $($rest:tt)+) => {{
+        let $p = $x;
+        $crate::c!(@nest $e; $($rest)+)
     }};
 
-    ($e:expr; $i1:pat in $iter1:expr, $i2:pat in $iter2:expr, $i3:pat in $iter3:expr) => {{
-        $iter1.flat_map(|$i1| $iter2.flat_map(move |$i2| $iter3.map(move |$i3| $e)))
+    // `enumerate` generators, rewritten to plain generators over the enumerated iterator.
+    (@nest $e:expr; $i:pat in enumerate $iter:expr $(, $($rest:tt)+)?) => {
+        $crate::c!(@nest $e; $i in ($iter).enumerate() $(, $($rest)+)?)
+    };
+
+    // `zip` generators, joined element-wise with the previous generator.
+    (@nest $e:expr; $i:pat in $iter:expr, zip $j:pat in enumerate $jter:expr $(, $($rest:tt)+)?) => {
+        $crate::c!(@nest $e; $i in $iter, zip $j in ($jter).enumerate() $(, $($rest)+)?)
+    };
+
+    (@nest $e:expr; $i:pat in $iter:expr, zip $j:pat in $jter:expr $(, $($rest:tt)+)?) => {
+        $crate::c!(@nest $e; ($i, $j) in ($iter).zip($jter) $(, $($rest)+)?)
+    };
+
+    // Inner generators, lowered to `move` closures so they can use the outer bindings.
+    (@nest $e:expr; $i:pat in $iter:expr) => {
+        $iter.map(move |$i| $e)
+    };
+
+    (@nest $e:expr; $i:pat in $iter:expr, if let $p:pat = $x:expr) => {
+        $iter.filter_map(move |$i| match $x {
+            $p => Some($e),
+            #[allow(unreachable_patterns)]
+            _ => None,
+        })
+    };
+
+    (@nest $e:expr; $i:pat in $iter:expr, $(if $cond:expr),+) => {
+        $iter.filter_map(move |$i| if $($cond)&&+ { Some($e) } else { None })
+    };
+
+    (@nest $e:expr; $i:pat in $iter:expr, $($rest:tt)+) => {
+        $iter.flat_map(move |$i| $crate::c!(@nest $e; $($rest)+))
+    };
+
+    ($e:expr; $i:pat in enumerate $iter:expr $(, $($rest:tt)+)?) => {
+        $crate::c!($e; $i in ($iter).enumerate() $(, $($rest)+)?)
+    };
+
+    ($e:expr; $i:pat in $iter:expr, zip $j:pat in enumerate $jter:expr $(, $($rest:tt)+)?) => {
+        $crate::c!($e; $i in $iter, zip $j in ($jter).enumerate() $(, $($rest)+)?)
+    };
+
+    ($e:expr; $i:pat in $iter:expr, zip $j:pat in $jter:expr $(, $($rest:tt)+)?) => {
+        $crate::c!($e; ($i, $j) in ($iter).zip($jter) $(, $($rest)+)?)
+    };
+
+    ($e:expr; $i:pat in $iter:expr) => {
+        $iter.map(|$i| $e)
+    };
+
+    ($e:expr; $i:pat in $iter:expr, if let $p:pat = $x:expr) => {{
+        $iter.filter_map(|$i| match $x {
+            $p => Some($e),
+            #[allow(unreachable_patterns)]
+            _ => None,
+        })
     }};
 
-    ($e:expr; $i1:pat in $iter1:expr, $i2:pat in $iter2:expr, $i3:pat in $iter3:expr, if $cond:expr) => {{
-        $iter1.flat_map(|$i1| {
-            $iter2.flat_map(move |$i2| {
-                $iter3.filter_map(move |$i3| if $cond { Some($e) } else { None })
+    ($e:expr; $i:pat in $iter:expr, $(if $cond:expr),+) => {{
+        $iter
+            $(.filter(|item| {
+                // Not every binding of the pattern has to be used by every clause.
+                #[allow(unused_variables)]
+                let $i = item;
+                $cond
+            }))+
+            .map(|item| {
+                #[allow(unused_variables)]
+                let $i = item;
+                $e
             })
-        })
+    }};
+
+    ($e:expr; $i:pat in $iter:expr, $($rest:tt)+) => {{
+        $iter.flat_map(|$i| $crate::c!(@nest $e; $($rest)+))
     }};
 }
 
 /// Build [`Vec`] from collection iterator comprehensions.
 ///
-/// ## Limitations
-///  * Only 3 nested comprehensions
+/// The [`Vec`] is preallocated with the lower bound of the comprehension's
+/// [`Iterator::size_hint`], so comprehensions of a known size are built with a single allocation.
 ///
 /// # Examples:
 /// ```
@@ -61,16 +163,16 @@ macro_rules! c {
 /// ```
 #[macro_export]
 macro_rules! cvec {
-    ($($tokens: tt)+) => {
-        $crate::c![$($tokens)+].collect::<::std::vec::Vec<_>>()
-    };
+    ($($tokens: tt)+) => {{
+        let iter = $crate::c![$($tokens)+];
+        let mut vec = ::std::vec::Vec::with_capacity(::std::iter::Iterator::size_hint(&iter).0);
+        ::std::iter::Extend::extend(&mut vec, iter);
+        vec
+    }};
 }
 
 /// Build [`VecDeque`] from collection iterator comprehensions.
 ///
-/// ## Limitations
-///  * Only 3 nested comprehensions
-///
 /// # Examples:
 /// ```
 /// use sugars::cdeque;
@@ -92,9 +194,6 @@ macro_rules! cdeque {
 
 /// Build [`LinkedList`] from collection iterator comprehensions.
 ///
-/// ## Limitations
-///  * Only 3 nested comprehensions
-///
 /// # Examples:
 /// ```
 /// use sugars::clkl;
@@ -116,9 +215,6 @@ macro_rules! clkl {
 
 /// Build [`BinaryHeap`] from collection iterator comprehensions.
 ///
-/// ## Limitations
-///  * Only 3 nested comprehensions
-///
 /// # Examples:
 /// ```
 /// use sugars::cbheap;
@@ -140,9 +236,6 @@ macro_rules! cbheap {
 
 /// Build [`HashMap`] from collection iterator comprehensions.
 ///
-/// ## Limitations
-///  * Only 3 nested comprehensions
-///
 /// # Examples:
 /// ```rust
 /// use sugars::cmap;
@@ -154,9 +247,34 @@ macro_rules! cbheap {
 /// # }
 /// ```
 ///
+/// The keys and values can also come from two separate iterators, zipped together. The map
+/// stops at the end of the shorter one. An optional `if |key, value| condition` guard keeps
+/// only the pairs matching the condition, with both bound by reference.
+///
+/// ```rust
+/// use sugars::cmap;
+///
+/// # fn main() {
+/// let w = cmap!(zip vec!["a", "b", "c"], vec![1, 2, 3]);
+/// let z = cmap!(zip vec!["a", "b", "c"], vec![1, 2, 3], if |_, v| v % 2 != 0);
+///
+/// assert_eq!(2, w["b"]);
+/// assert_eq!(2, z.len());
+/// # }
+/// ```
+///
 /// [`HashMap`]: ::std::collections::HashMap
 #[macro_export]
 macro_rules! cmap {
+    (zip $keys:expr, $values:expr $(,)?) => {{
+        ::std::iter::Iterator::zip(::std::iter::IntoIterator::into_iter($keys), $values)
+            .collect::<::std::collections::HashMap<_, _>>()
+    }};
+    (zip $keys:expr, $values:expr, if |$k:pat, $v:pat| $cond:expr $(,)?) => {{
+        ::std::iter::Iterator::zip(::std::iter::IntoIterator::into_iter($keys), $values)
+            .filter(|($k, $v)| $cond)
+            .collect::<::std::collections::HashMap<_, _>>()
+    }};
     ($key:expr => $value:expr; $($tokens: tt)+) => {{
         $crate::c![ ($key, $value); $($tokens)+ ].collect::<::std::collections::HashMap<_, _>>()
     }};
@@ -164,9 +282,6 @@ macro_rules! cmap {
 
 /// Build [`HashSet`] from collection iterator comprehensions.
 ///
-/// ## Limitations
-///  * Only 3 nested comprehensions
-///
 /// # Examples:
 /// ```rust
 /// use sugars::cset;
@@ -187,9 +302,6 @@ macro_rules! cset {
 
 /// Build [`BTreeMap`] from collection iterator comprehensions.
 ///
-/// ## Limitations
-///  * Only 3 nested comprehensions
-///
 /// # Examples:
 /// ```rust
 /// use sugars::cbtmap;
@@ -211,9 +323,6 @@ macro_rules! cbtmap {
 
 /// Build [`BTreeSet`] from collection iterator comprehensions.
 ///
-/// ## Limitations
-///  * Only 3 nested comprehensions
-///
 /// # Examples:
 /// ```rust
 /// use sugars::cbtset;
@@ -232,257 +341,2701 @@ macro_rules! cbtset {
     }};
 }
 
-#[cfg(test)]
-mod tests {
-    use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
-
-    #[test]
-    fn c_no_conditional() {
-        let expected = vec![2, 4, 6, 8];
-        let test: Vec<_> = c![x*2; x in 1..5].collect();
+/// Build [`String`] from collection iterator comprehensions.
+///
+/// The produced items can be anything a [`String`] can be collected from, like [`char`] or
+/// [`&str`].
+///
+/// # Examples:
+/// ```rust
+/// use sugars::cstring;
+///
+/// # fn main() {
+/// let w = cstring![c.to_ascii_uppercase(); c in "hello".chars()];
+/// let z = cstring![c; c in "a b c".chars(), if !c.is_whitespace()];
+///
+/// assert_eq!("HELLO", w);
+/// assert_eq!("abc", z);
+/// # }
+/// ```
+///
+/// [`String`]: ::std::string::String
+/// [`&str`]: str
+#[macro_export]
+macro_rules! cstring {
+    ($($tokens: tt)+) => {{
+        $crate::c![$($tokens)+].collect::<::std::string::String>()
+    }};
+}
 
-        assert_eq!(expected, test);
-    }
+/// Build [`Result`]`<`[`Vec`]`<T>, E>` from collection iterator comprehensions producing
+/// [`Result`]s.
+///
+/// It stops at the first [`Err`] and returns it, otherwise all the [`Ok`] values are collected.
+///
+/// # Examples:
+/// ```rust
+/// use sugars::cresult;
+///
+/// # fn main() {
+/// let w = cresult![s.parse::<i32>(); s in vec!["1", "2", "3"].into_iter()];
+/// let z = cresult![s.parse::<i32>(); s in vec!["1", "x", "3"].into_iter(), if !s.is_empty()];
+///
+/// assert_eq!(Ok(vec![1, 2, 3]), w);
+/// assert!(z.is_err());
+/// # }
+/// ```
+///
+/// [`Vec`]: ::std::vec::Vec
+/// [`Result`]: ::std::result::Result
+#[macro_export]
+macro_rules! cresult {
+    ($($tokens: tt)+) => {{
+        $crate::c![$($tokens)+].collect::<::std::result::Result<::std::vec::Vec<_>, _>>()
+    }};
+}
 
-    #[test]
-    fn c_with_conditional() {
-        let expected = vec![0, 2, 4, 6, 8];
-        let test: Vec<_> = c![x; x in 0..10, if x%2 == 0].collect();
+/// Build a dense [`Vec`] from `(index, value)` pairs of a collection iterator comprehension.
+///
+/// The vector is created with length `len` filled with clones of `default`, and each value is
+/// written at its index. Later pairs overwrite earlier ones at the same index.
+///
+/// It accepts the pairs destructured directly in the generator, or the `index => value` form,
+/// like [`cmap`].
+///
+/// # Panics
+/// Panics if any produced index is out of the `0..len` range.
+///
+/// # Examples:
+/// ```rust
+/// use sugars::cdense;
+///
+/// # fn main() {
+/// let pairs = vec![(0, 'a'), (3, 'd')];
+/// let w = cdense!(5, ' '; (i, v) in pairs.into_iter());
+/// assert_eq!(vec!['a', ' ', ' ', 'd', ' '], w);
+///
+/// let z = cdense!(4, 0; x => x * 10; x in 0..4, if x % 2 == 0);
+/// assert_eq!(vec![0, 0, 20, 0], z);
+/// # }
+/// ```
+///
+/// [`cmap`]: crate::cmap
+#[macro_export]
+macro_rules! cdense {
+    ($len:expr, $default:expr; ($i:ident, $v:ident) in $($tokens: tt)+) => {
+        $crate::cdense!($len, $default; $i => $v; ($i, $v) in $($tokens)+)
+    };
 
-        assert_eq!(expected, test);
-    }
+    ($len:expr, $default:expr; $index:expr => $value:expr; $($tokens: tt)+) => {{
+        let mut dense = ::std::vec![$default; $len];
+        for (i, v) in $crate::c![($index, $value); $($tokens)+] {
+            dense[i] = v;
+        }
+        dense
+    }};
+}
 
-    #[test]
-    fn c_2_nested_no_conditional() {
-        let expected = vec![1, 2, 3, 4, 5, 6, 7, 8, 9];
-        let nested = vec![vec![1, 2, 3], vec![4, 5, 6], vec![7, 8, 9]];
-        let test: Vec<_> = c![x; y in nested.into_iter(), x in y.into_iter()].collect();
+/// Build a [`Vec`] of simple moving averages over a window of the given size from collection
+/// iterator comprehensions.
+///
+/// The produced items must convert into [`f64`]. Positions before the window is full hold the
+/// average of the items seen so far, so the output has the same length as the input.
+///
+/// It also accepts the [`x in iter` shorthand](crate#shorthand-generator).
+///
+/// # Panics
+/// Panics if the window size is zero.
+///
+/// # Examples:
+/// ```rust
+/// use sugars::cmovavg;
+///
+/// # fn main() {
+/// let w = cmovavg!(2; x in vec![1, 3, 5, 7].into_iter());
+/// assert_eq!(vec![1.0, 2.0, 4.0, 6.0], w);
+///
+/// let z = cmovavg!(3; x * 2; x in 1..=4);
+/// assert_eq!(vec![2.0, 3.0, 4.0, 6.0], z);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! cmovavg {
+    ($window:expr; $x:ident in $($tokens: tt)+) => {
+        $crate::cmovavg!($window; $x; $x in $($tokens)+)
+    };
 
-        assert_eq!(expected, test);
-    }
+    ($window:expr; $($tokens: tt)+) => {{
+        let window: usize = $window;
+        assert!(window > 0, "cmovavg!: window size must be greater than zero");
+        let mut acc = ::std::collections::VecDeque::with_capacity(window);
+        let mut sum = 0.0;
+        $crate::c![$($tokens)+]
+            .map(|x| {
+                let x: f64 = ::std::convert::Into::into(x);
+                if acc.len() == window {
+                    sum -= acc.pop_front().unwrap_or(0.0);
+                }
+                acc.push_back(x);
+                sum += x;
+                sum / acc.len() as f64
+            })
+            .collect::<::std::vec::Vec<f64>>()
+    }};
+}
 
-    #[test]
-    fn c_2_nested_with_conditional() {
-        let expected = vec![2, 4, 6, 8];
-        let nested = vec![vec![1, 2, 3], vec![4, 5, 6], vec![7, 8, 9]];
-        let test: Vec<_> =
-            c![x; y in nested.into_iter(), x in y.into_iter(), if x % 2 == 0].collect();
+/// Build a [`Vec`] overlaying an iterator of optional overrides onto an iterator of defaults.
+///
+/// Each position takes the override when it is `Some`, otherwise the default. The result is
+/// truncated to the shorter of the two iterators.
+///
+/// # Examples:
+/// ```rust
+/// use sugars::coverlay;
+///
+/// # fn main() {
+/// let defaults = vec![1, 2, 3, 4];
+/// let overrides = vec![None, Some(20), None, Some(40)];
+///
+/// let w = coverlay!(defaults.into_iter(), overrides.into_iter());
+/// assert_eq!(vec![1, 20, 3, 40], w);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! coverlay {
+    ($defaults:expr, $overrides:expr $(,)?) => {{
+        $defaults
+            .into_iter()
+            .zip($overrides)
+            .map(|(default, over)| over.unwrap_or(default))
+            .collect::<::std::vec::Vec<_>>()
+    }};
+}
 
-        assert_eq!(expected, test);
-    }
+/// Build a [`Vec`] from collection iterator comprehensions, collapsing consecutive items
+/// considered equal by the given function into the first item of each run.
+///
+/// The function receives references to the first item of the current run and to the next
+/// item. Equal items that are not adjacent are kept.
+///
+/// It also accepts the [`x in iter` shorthand](crate#shorthand-generator).
+///
+/// # Examples:
+/// ```rust
+/// use sugars::cdedup_by;
+///
+/// # fn main() {
+/// let w = cdedup_by!(|a, b| a / 10 == b / 10; x in vec![10, 12, 25, 21, 13].into_iter());
+/// assert_eq!(vec![10, 25, 13], w);
+///
+/// let z = cdedup_by!(|a, b| a == b; x % 3; x in 0..9, if x % 2 == 0);
+/// assert_eq!(vec![0, 2, 1, 0, 2], z);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! cdedup_by {
+    ($f:expr; $x:ident in $($tokens: tt)+) => {
+        $crate::cdedup_by!($f; $x; $x in $($tokens)+)
+    };
 
-    #[test]
-    fn c_3_nested_no_conditional() {
-        let expected = vec![
-            (1, 1, 1),
-            (1, 1, 2),
-            (1, 1, 3),
-            (1, 1, 4),
-            (1, 2, 2),
-            (1, 2, 3),
-            (1, 2, 4),
-            (1, 3, 3),
-            (1, 3, 4),
-            (1, 4, 4),
-            (2, 2, 2),
-            (2, 2, 3),
-            (2, 2, 4),
-            (2, 3, 3),
-            (2, 3, 4),
-            (2, 4, 4),
-            (3, 3, 3),
-            (3, 3, 4),
-            (3, 4, 4),
-            (4, 4, 4),
-        ];
-        let n: i32 = 4;
-        let test: Vec<_> = c![(x, y, z); x in 1..=n, y in x..=n, z in y..=n].collect();
+    ($f:expr; $($tokens: tt)+) => {{
+        fn dedup_by<T, F: FnMut(&T, &T) -> bool>(vec: &mut ::std::vec::Vec<T>, mut f: F) {
+            vec.dedup_by(|next, first| f(first, next));
+        }
+        let mut vec = $crate::c![$($tokens)+].collect::<::std::vec::Vec<_>>();
+        dedup_by(&mut vec, $f);
+        vec
+    }};
+}
 
-        assert_eq!(expected, test);
-    }
+/// Build a [`Vec`] from collection iterator comprehensions while folding the produced items,
+/// returning both as a `(Vec<T>, Acc)` tuple.
+///
+/// The folding function receives the accumulator and a reference to each produced item, so
+/// everything is done in a single pass.
+///
+/// It also accepts the [`x in iter` shorthand](crate#shorthand-generator).
+///
+/// # Examples:
+/// ```rust
+/// use sugars::caccum;
+///
+/// # fn main() {
+/// let (w, sum) = caccum!(0, |acc, x| acc + x; x in 1..5);
+/// assert_eq!(vec![1, 2, 3, 4], w);
+/// assert_eq!(10, sum);
+///
+/// let (z, len) = caccum!(0, |acc, s: &String| acc + s.len(); x.to_string(); x in 8..12);
+/// assert_eq!(vec!["8", "9", "10", "11"], z);
+/// assert_eq!(6, len);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! caccum {
+    ($init:expr, $f:expr; $x:ident in $($tokens: tt)+) => {
+        $crate::caccum!($init, $f; $x; $x in $($tokens)+)
+    };
 
-    #[test]
-    fn c_3_nested_with_conditional() {
-        let expected = vec![(3, 4, 5), (6, 8, 10)];
-        let n: i32 = 10;
-        let test: Vec<_> =
-            c![(x,y, z); x in 1..=n, y in x..=n, z in y..=n, if x.pow(2) + y.pow(2) == z.pow(2)]
-                .collect();
+    ($init:expr, $f:expr; $($tokens: tt)+) => {{
+        fn accumulate<T, A, I, F>(iter: I, init: A, mut f: F) -> (::std::vec::Vec<T>, A)
+        where
+            I: ::std::iter::Iterator<Item = T>,
+            F: FnMut(A, &T) -> A,
+        {
+            let mut vec = ::std::vec::Vec::with_capacity(iter.size_hint().0);
+            let mut acc = init;
+            for item in iter {
+                acc = f(acc, &item);
+                vec.push(item);
+            }
+            (vec, acc)
+        }
+        accumulate($crate::c![$($tokens)+], $init, $f)
+    }};
+}
 
-        assert_eq!(expected, test);
-    }
+/// Build a [`Vec`] with the positions of the items of an iterator for which the condition holds.
+///
+/// The positions are 0-based indices into the iterator, and the condition is required.
+///
+/// # Examples:
+/// ```rust
+/// use sugars::cpositions;
+///
+/// # fn main() {
+/// let w = cpositions![x in vec![3, 8, 5, 2].into_iter(), if x % 2 == 0];
+/// assert_eq!(vec![1, 3], w);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! cpositions {
+    ($i:pat in $iter:expr, if $cond:expr) => {{
+        $iter
+            .enumerate()
+            .filter_map(|(pos, $i)| if $cond { Some(pos) } else { None })
+            .collect::<::std::vec::Vec<usize>>()
+    }};
+}
 
-    #[test]
-    fn cvec_basic_no_conditional() {
-        let expected = vec![2, 4, 6, 8];
+/// Split the items of an iterator in two [`Vec`]s around the first item for which the condition
+/// holds, returning them as a tuple.
+///
+/// The first vector holds the items before the first match, and the second holds the rest,
+/// including the matching item itself. If no item matches, every item goes to the first vector.
+///
+/// Since every item is kept in one of the vectors, the pattern binds a reference to the item
+/// inside the condition, unlike in [`cpositions`] where it binds the item itself.
+///
+/// # Examples:
+/// ```rust
+/// use sugars::csplit_first;
+///
+/// # fn main() {
+/// let lines = vec!["Host: a", "Accept: b", "", "body", ""];
+/// let (headers, body) = csplit_first![l in lines.into_iter(), if l.is_empty()];
+///
+/// assert_eq!(vec!["Host: a", "Accept: b"], headers);
+/// assert_eq!(vec!["", "body", ""], body);
+/// # }
+/// ```
+///
+/// [`cpositions`]: crate::cpositions
+#[macro_export]
+macro_rules! csplit_first {
+    ($i:pat in $iter:expr, if $cond:expr) => {{
+        let (head, tail, _) = $iter.fold(
+            (::std::vec::Vec::new(), ::std::vec::Vec::new(), false),
+            |(mut head, mut tail, mut found), item| {
+                if !found {
+                    let $i = &item;
+                    found = $cond;
+                }
+                if found {
+                    tail.push(item);
+                } else {
+                    head.push(item);
+                }
+                (head, tail, found)
+            },
+        );
+        (head, tail)
+    }};
+}
+
+/// Build a [`Vec`] of index-value pairs of an iterator, keeping only those for which the
+/// condition holds.
+///
+/// The generator binds both the 0-based index and the item, as in `(i, x) in iter`, and the
+/// condition can use both of them. Since the pairs are kept in the result, they are bound as
+/// references inside the condition.
+///
+/// # Examples:
+/// ```rust
+/// use sugars::cidx_filter;
+///
+/// # fn main() {
+/// let w = cidx_filter![(i, x) in vec![3, -1, 4, 1, -5].into_iter(), if i % 2 == 0 && *x > 0];
+/// assert_eq!(vec![(0, 3), (2, 4)], w);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! cidx_filter {
+    (($i:ident, $x:ident) in $iter:expr, if $cond:expr) => {{
+        $iter
+            .enumerate()
+            .filter(|($i, $x)| $cond)
+            .collect::<::std::vec::Vec<(usize, _)>>()
+    }};
+}
+
+/// Build [`Vec`] from collection iterator comprehensions, inserting a clone of the separator
+/// between each pair of produced items.
+///
+/// # Examples:
+/// ```rust
+/// use sugars::cintersperse;
+///
+/// # fn main() {
+/// let w = cintersperse!(0; x; x in 1..=3);
+/// assert_eq!(vec![1, 0, 2, 0, 3], w);
+///
+/// let z = cintersperse!(", "; s; s in vec!["a", "b"].into_iter());
+/// assert_eq!("a, b", z.concat());
+/// # }
+/// ```
+#[macro_export]
+macro_rules! cintersperse {
+    ($sep:expr; $($tokens: tt)+) => {{
+        let sep = $sep;
+        let mut vec = ::std::vec::Vec::new();
+        for item in $crate::c![$($tokens)+] {
+            if !vec.is_empty() {
+                vec.push(::std::clone::Clone::clone(&sep));
+            }
+            vec.push(item);
+        }
+        vec
+    }};
+}
+
+/// Build the run-length encoding of collection iterator comprehensions, as a [`Vec`] of
+/// `(item, run_length)` tuples.
+///
+/// It also accepts the [`x in iter` shorthand](crate#shorthand-generator).
+///
+/// # Examples:
+/// ```rust
+/// use sugars::crle;
+///
+/// # fn main() {
+/// let w = crle![x in vec![1, 1, 2, 3, 3, 3].into_iter()];
+/// assert_eq!(vec![(1, 2), (2, 1), (3, 3)], w);
+///
+/// let z = crle![x / 3; x in 0..7];
+/// assert_eq!(vec![(0, 3), (1, 3), (2, 1)], z);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! crle {
+    ($x:ident in $($tokens: tt)+) => {
+        $crate::crle![$x; $x in $($tokens)+]
+    };
+
+    ($($tokens: tt)+) => {{
+        let mut runs: ::std::vec::Vec<(_, usize)> = ::std::vec::Vec::new();
+        for item in $crate::c![$($tokens)+] {
+            match runs.last_mut() {
+                Some((last, count)) if *last == item => *count += 1,
+                _ => runs.push((item, 1)),
+            }
+        }
+        runs
+    }};
+}
+
+/// Build a [`HashMap`] with the number of distinct values produced for each key from collection
+/// iterator comprehensions.
+///
+/// The values must implement [`Eq`] and [`Hash`].
+///
+/// # Examples:
+/// ```rust
+/// use sugars::cdistinct_counts;
+///
+/// # fn main() {
+/// let visits = vec![("home", "ana"), ("home", "bob"), ("home", "ana"), ("about", "ana")];
+/// let w = cdistinct_counts!{page => user; (page, user) in visits.into_iter()};
+///
+/// assert_eq!(2, w["home"]);
+/// assert_eq!(1, w["about"]);
+/// # }
+/// ```
+///
+/// [`HashMap`]: ::std::collections::HashMap
+/// [`Hash`]: ::std::hash::Hash
+#[macro_export]
+macro_rules! cdistinct_counts {
+    ($key:expr => $value:expr; $($tokens: tt)+) => {{
+        let mut sets = ::std::collections::HashMap::new();
+        for (k, v) in $crate::c![($key, $value); $($tokens)+] {
+            sets.entry(k)
+                .or_insert_with(::std::collections::HashSet::new)
+                .insert(v);
+        }
+        sets.into_iter()
+            .map(|(k, set)| (k, set.len()))
+            .collect::<::std::collections::HashMap<_, usize>>()
+    }};
+}
+
+/// Build a [`Vec`] with the sums of each window of the given size over collection iterator
+/// comprehensions.
+///
+/// The produced items must implement [`Add`], [`Sub`] and [`Copy`], as a running total is kept
+/// to compute every sum in constant time. The output has `len - window + 1` items, so it is
+/// empty if the window is larger than the number of produced items.
+///
+/// It also accepts the [`x in iter` shorthand](crate#shorthand-generator).
+///
+/// # Panics
+/// Panics if the window size is zero.
+///
+/// # Examples:
+/// ```rust
+/// use sugars::cwinsum;
+///
+/// # fn main() {
+/// let w = cwinsum!(2; x in vec![1, 2, 3, 4].into_iter());
+/// assert_eq!(vec![3, 5, 7], w);
+/// # }
+/// ```
+///
+/// [`Add`]: ::std::ops::Add
+/// [`Sub`]: ::std::ops::Sub
+#[macro_export]
+macro_rules! cwinsum {
+    ($window:expr; $x:ident in $($tokens: tt)+) => {
+        $crate::cwinsum!($window; $x; $x in $($tokens)+)
+    };
+
+    ($window:expr; $($tokens: tt)+) => {{
+        let window: usize = $window;
+        assert!(window > 0, "cwinsum!: window size must be greater than zero");
+        let mut buf = ::std::collections::VecDeque::with_capacity(window);
+        let mut total = None;
+        let mut sums = ::std::vec::Vec::new();
+        for item in $crate::c![$($tokens)+] {
+            let popped = if buf.len() == window {
+                buf.pop_front()
+            } else {
+                None
+            };
+            buf.push_back(item);
+            let sum = match (total, popped) {
+                (Some(sum), Some(old)) => sum + item - old,
+                (Some(sum), None) => sum + item,
+                (None, _) => item,
+            };
+            if buf.len() == window {
+                sums.push(sum);
+            }
+            total = Some(sum);
+        }
+        sums
+    }};
+}
+
+/// Build a [`HashMap`] from collection iterator comprehensions, reducing all the values
+/// produced for the same key with the given function.
+///
+/// The first value of each key is used as the initial accumulator, and each following value is
+/// combined with it by calling the function with the accumulator and the new value.
+///
+/// # Examples:
+/// ```rust
+/// use sugars::creduce;
+///
+/// # fn main() {
+/// let scores = vec![("ana", 3), ("bob", 5), ("ana", 7), ("bob", 1)];
+/// let w = creduce!(|acc, v| acc.max(v); k => v; (k, v) in scores.into_iter());
+///
+/// assert_eq!(7, w["ana"]);
+/// assert_eq!(5, w["bob"]);
+/// # }
+/// ```
+///
+/// [`HashMap`]: ::std::collections::HashMap
+#[macro_export]
+macro_rules! creduce {
+    ($f:expr; $key:expr => $value:expr; $($tokens: tt)+) => {{
+        fn reduce<K, V, I, F>(iter: I, mut f: F) -> ::std::collections::HashMap<K, V>
+        where
+            K: ::std::cmp::Eq + ::std::hash::Hash,
+            I: ::std::iter::Iterator<Item = (K, V)>,
+            F: FnMut(V, V) -> V,
+        {
+            // The accumulators are kept in an `Option` so they can be moved out of the map to be
+            // reduced, and every slot holds a value again once the key is processed.
+            let mut map = ::std::collections::HashMap::new();
+            for (k, v) in iter {
+                let slot = map.entry(k).or_insert_with(|| None);
+                *slot = Some(match slot.take() {
+                    Some(acc) => f(acc, v),
+                    None => v,
+                });
+            }
+            map.into_iter()
+                .filter_map(|(k, v): (K, Option<V>)| Some((k, v?)))
+                .collect()
+        }
+        reduce($crate::c![($key, $value); $($tokens)+], $f)
+    }};
+}
+
+/// Build a [`Vec`] with the `n` largest items of collection iterator comprehensions, sorted in
+/// descending order.
+///
+/// The items must implement [`Ord`]. Only `n` items are kept at a time in a bounded min-heap, so
+/// the whole input is never sorted. If fewer than `n` items are produced, all of them are
+/// returned.
+///
+/// It also accepts the [`x in iter` shorthand](crate#shorthand-generator).
+///
+/// # Examples:
+/// ```rust
+/// use sugars::ctopn;
+///
+/// # fn main() {
+/// let w = ctopn!(3; x in vec![5, 1, 9, 7, 3].into_iter());
+/// assert_eq!(vec![9, 7, 5], w);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! ctopn {
+    ($n:expr; $x:ident in $($tokens: tt)+) => {
+        $crate::ctopn!($n; $x; $x in $($tokens)+)
+    };
+
+    ($n:expr; $($tokens: tt)+) => {{
+        use ::std::cmp::Reverse;
+        let n: usize = $n;
+        let mut heap = ::std::collections::BinaryHeap::with_capacity(n);
+        for item in $crate::c![$($tokens)+] {
+            if heap.len() < n {
+                heap.push(Reverse(item));
+            } else if heap.peek().is_some_and(|Reverse(min)| item > *min) {
+                heap.pop();
+                heap.push(Reverse(item));
+            }
+        }
+        heap.into_sorted_vec()
+            .into_iter()
+            .map(|Reverse(item)| item)
+            .collect::<::std::vec::Vec<_>>()
+    }};
+}
+
+/// Keeps the `n` items with the largest keys, sorted by key in descending order.
+///
+/// Used by the [`ctopn_by`] macro.
+///
+/// [`ctopn_by`]: crate::ctopn_by
+#[doc(hidden)]
+pub fn top_n_by<T, K, I, F>(iter: I, n: usize, mut f: F) -> Vec<T>
+where
+    I: Iterator<Item = T>,
+    K: Ord,
+    F: FnMut(&T) -> K,
+{
+    use std::{
+        cmp::{Ordering, Reverse},
+        collections::BinaryHeap,
+    };
+
+    struct Entry<K, T>(K, T);
+
+    impl<K: Ord, T> PartialEq for Entry<K, T> {
+        fn eq(&self, other: &Self) -> bool {
+            self.0 == other.0
+        }
+    }
+
+    impl<K: Ord, T> Eq for Entry<K, T> {}
+
+    impl<K: Ord, T> PartialOrd for Entry<K, T> {
+        fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+
+    impl<K: Ord, T> Ord for Entry<K, T> {
+        fn cmp(&self, other: &Self) -> Ordering {
+            self.0.cmp(&other.0)
+        }
+    }
+
+    let mut heap = BinaryHeap::with_capacity(n);
+    for item in iter {
+        let key = f(&item);
+        if heap.len() < n {
+            heap.push(Reverse(Entry(key, item)));
+        } else if heap.peek().is_some_and(|Reverse(min)| key > min.0) {
+            heap.pop();
+            heap.push(Reverse(Entry(key, item)));
+        }
+    }
+    heap.into_sorted_vec()
+        .into_iter()
+        .map(|Reverse(Entry(_, item))| item)
+        .collect()
+}
+
+/// Build a [`Vec`] with the `n` items with the largest keys of collection iterator
+/// comprehensions, sorted by key in descending order.
+///
+/// The key function receives a reference to each item, and the keys must implement [`Ord`].
+/// Like [`ctopn`], only `n` items are kept at a time.
+///
+/// It also accepts the [`x in iter` shorthand](crate#shorthand-generator).
+///
+/// # Examples:
+/// ```rust
+/// use sugars::ctopn_by;
+///
+/// # fn main() {
+/// let players = vec![("ana", 30), ("bob", 50), ("eve", 40)];
+/// let w = ctopn_by!(2, |p| p.1; p in players.into_iter());
+/// assert_eq!(vec![("bob", 50), ("eve", 40)], w);
+/// # }
+/// ```
+///
+/// [`ctopn`]: crate::ctopn
+#[macro_export]
+macro_rules! ctopn_by {
+    ($n:expr, $f:expr; $x:ident in $($tokens: tt)+) => {
+        $crate::ctopn_by!($n, $f; $x; $x in $($tokens)+)
+    };
+
+    ($n:expr, $f:expr; $($tokens: tt)+) => {
+        $crate::top_n_by($crate::c![$($tokens)+], $n, $f)
+    };
+}
+
+/// Build a two-level [`HashMap`] from collection iterator comprehensions, inserting each value
+/// under its outer and inner keys.
+///
+/// Both key types must implement [`Eq`] and [`Hash`]. If the same pair of keys is produced more
+/// than once, the last value wins.
+///
+/// # Examples:
+/// ```rust
+/// use sugars::cnested_map;
+///
+/// # fn main() {
+/// let sales = vec![("2020", "jan", 10), ("2020", "feb", 12), ("2021", "jan", 15)];
+/// let w = cnested_map!{year, month => total; (year, month, total) in sales.into_iter()};
+///
+/// assert_eq!(12, w["2020"]["feb"]);
+/// assert_eq!(15, w["2021"]["jan"]);
+/// # }
+/// ```
+///
+/// [`HashMap`]: ::std::collections::HashMap
+/// [`Hash`]: ::std::hash::Hash
+#[macro_export]
+macro_rules! cnested_map {
+    ($outer:expr, $inner:expr => $value:expr; $($tokens: tt)+) => {{
+        let mut map = ::std::collections::HashMap::new();
+        for (outer, inner, value) in $crate::c![($outer, $inner, $value); $($tokens)+] {
+            map.entry(outer)
+                .or_insert_with(::std::collections::HashMap::new)
+                .insert(inner, value);
+        }
+        map
+    }};
+}
+
+/// Build [`Vec`] with the [`Ok`] values of collection iterator comprehensions, passing every
+/// [`Err`] to the given callback instead.
+///
+/// # Examples:
+/// ```rust
+/// use sugars::cvec_log_errs;
+///
+/// # fn main() {
+/// let input = vec!["1", "two", "3"];
+/// let w = cvec_log_errs!(|e| eprintln!("{}", e); x.parse::<i32>(); x in input.into_iter());
+///
+/// assert_eq!(vec![1, 3], w);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! cvec_log_errs {
+    ($f:expr; $($tokens: tt)+) => {{
+        fn log_errs<T, E, I, F>(iter: I, mut f: F) -> ::std::vec::Vec<T>
+        where
+            I: ::std::iter::Iterator<Item = ::std::result::Result<T, E>>,
+            F: FnMut(E),
+        {
+            let mut oks = ::std::vec::Vec::with_capacity(iter.size_hint().0);
+            for item in iter {
+                match item {
+                    ::std::result::Result::Ok(value) => oks.push(value),
+                    ::std::result::Result::Err(err) => f(err),
+                }
+            }
+            oks
+        }
+        log_errs($crate::c![$($tokens)+], $f)
+    }};
+}
+
+/// Build a [`HashMap`] grouping the values of collection iterator comprehensions by key, with
+/// the values of each key sorted.
+///
+/// The keys must implement [`Eq`] and [`Hash`], and the values must implement [`Ord`].
+///
+/// # Examples:
+/// ```rust
+/// use sugars::cgroup_sorted;
+///
+/// # fn main() {
+/// let scores = vec![("ana", 7), ("bob", 5), ("ana", 3), ("bob", 1)];
+/// let w = cgroup_sorted!{k => v; (k, v) in scores.into_iter()};
+///
+/// assert_eq!(vec![3, 7], w["ana"]);
+/// assert_eq!(vec![1, 5], w["bob"]);
+/// # }
+/// ```
+///
+/// [`HashMap`]: ::std::collections::HashMap
+/// [`Hash`]: ::std::hash::Hash
+#[macro_export]
+macro_rules! cgroup_sorted {
+    ($key:expr => $value:expr; $($tokens: tt)+) => {{
+        let mut map = ::std::collections::HashMap::new();
+        for (k, v) in $crate::c![($key, $value); $($tokens)+] {
+            map.entry(k).or_insert_with(::std::vec::Vec::new).push(v);
+        }
+        for values in map.values_mut() {
+            values.sort();
+        }
+        map
+    }};
+}
+
+/// Build [`Vec`] from collection iterator comprehensions, repeating each produced item `n`
+/// times in a row.
+///
+/// The items must implement [`Clone`].
+///
+/// It also accepts the [`x in iter` shorthand](crate#shorthand-generator).
+///
+/// # Examples:
+/// ```rust
+/// use sugars::crepeat_each;
+///
+/// # fn main() {
+/// let w = crepeat_each!(2; x in 1..=3);
+/// assert_eq!(vec![1, 1, 2, 2, 3, 3], w);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! crepeat_each {
+    ($n:expr; $x:ident in $($tokens: tt)+) => {
+        $crate::crepeat_each!($n; $x; $x in $($tokens)+)
+    };
+
+    ($n:expr; $($tokens: tt)+) => {{
+        let n: usize = $n;
+        $crate::c![$($tokens)+]
+            .flat_map(|item| ::std::iter::repeat(item).take(n))
+            .collect::<::std::vec::Vec<_>>()
+    }};
+}
+
+/// Build a [`HashSet`] with the elements that are in exactly one of two [`HashSet`]s.
+///
+/// The elements must implement [`Eq`], [`Hash`] and [`Clone`]. The sets are only borrowed.
+///
+/// # Examples:
+/// ```rust
+/// use std::collections::HashSet;
+/// use sugars::{csymdiff, hset};
+///
+/// # fn main() {
+/// let a = hset! {1, 2, 3};
+/// let b = hset! {2, 3, 4};
+/// let w = csymdiff!(a, b);
+///
+/// assert_eq!(hset! {1, 4}, w);
+/// # }
+/// ```
+///
+/// [`HashSet`]: ::std::collections::HashSet
+/// [`Hash`]: ::std::hash::Hash
+#[macro_export]
+macro_rules! csymdiff {
+    ($a:expr, $b:expr $(,)?) => {
+        $a.symmetric_difference(&$b)
+            .cloned()
+            .collect::<::std::collections::HashSet<_>>()
+    };
+}
+
+/// Build a [`BTreeSet`] with the elements that are in exactly one of two [`BTreeSet`]s.
+///
+/// The elements must implement [`Ord`] and [`Clone`]. The sets are only borrowed.
+///
+/// # Examples:
+/// ```rust
+/// use sugars::{btset, cbtsymdiff};
+///
+/// # fn main() {
+/// let a = btset! {1, 2, 3};
+/// let b = btset! {2, 3, 4};
+/// let w = cbtsymdiff!(a, b);
+///
+/// assert_eq!(vec![1, 4], w.into_iter().collect::<Vec<_>>());
+/// # }
+/// ```
+///
+/// [`BTreeSet`]: ::std::collections::BTreeSet
+#[macro_export]
+macro_rules! cbtsymdiff {
+    ($a:expr, $b:expr $(,)?) => {
+        $a.symmetric_difference(&$b)
+            .cloned()
+            .collect::<::std::collections::BTreeSet<_>>()
+    };
+}
+
+/// Build [`Vec`] with the differences between consecutive items of collection iterator
+/// comprehensions.
+///
+/// The items must implement [`Sub`] and [`Copy`]. Each difference is the item minus the one
+/// before it, so fewer than two items produce an empty [`Vec`].
+///
+/// It also accepts the [`x in iter` shorthand](crate#shorthand-generator).
+///
+/// # Examples:
+/// ```rust
+/// use sugars::cdiff;
+///
+/// # fn main() {
+/// let w = cdiff![x in vec![1, 3, 6, 10].into_iter()];
+/// assert_eq!(vec![2, 3, 4], w);
+/// # }
+/// ```
+///
+/// [`Sub`]: ::std::ops::Sub
+#[macro_export]
+macro_rules! cdiff {
+    ($x:ident in $($tokens: tt)+) => {
+        $crate::cdiff![$x; $x in $($tokens)+]
+    };
+
+    ($($tokens: tt)+) => {{
+        let mut prev = ::std::option::Option::None;
+        let mut diffs = ::std::vec::Vec::new();
+        for item in $crate::c![$($tokens)+] {
+            if let ::std::option::Option::Some(prev) = prev {
+                diffs.push(item - prev);
+            }
+            prev = ::std::option::Option::Some(item);
+        }
+        diffs
+    }};
+}
+
+/// Build a [`HashMap`] from each distinct item of collection iterator comprehensions to the
+/// position where it first appeared.
+///
+/// The items must implement [`Eq`] and [`Hash`]. Later occurrences of an item are ignored.
+///
+/// It also accepts the [`x in iter` shorthand](crate#shorthand-generator).
+///
+/// # Examples:
+/// ```rust
+/// use sugars::cindex;
+///
+/// # fn main() {
+/// let w = cindex![c in "abcab".chars()];
+///
+/// assert_eq!(0, w[&'a']);
+/// assert_eq!(1, w[&'b']);
+/// assert_eq!(2, w[&'c']);
+/// # }
+/// ```
+///
+/// [`HashMap`]: ::std::collections::HashMap
+/// [`Hash`]: ::std::hash::Hash
+#[macro_export]
+macro_rules! cindex {
+    ($x:ident in $($tokens: tt)+) => {
+        $crate::cindex![$x; $x in $($tokens)+]
+    };
+
+    ($($tokens: tt)+) => {{
+        let mut map = ::std::collections::HashMap::new();
+        for (i, item) in $crate::c![$($tokens)+].enumerate() {
+            map.entry(item).or_insert(i);
+        }
+        map
+    }};
+}
+
+/// Build [`Vec`] of [`f64`] from collection iterator comprehensions, rescaling the items to the
+/// `[0, 1]` range based on the smallest and largest items.
+///
+/// The items must implement [`Into`]`<f64>`. The smallest item maps to `0.0` and the largest to
+/// `1.0`. If all the items are equal, they all map to `0.0`.
+///
+/// It also accepts the [`x in iter` shorthand](crate#shorthand-generator).
+///
+/// # Examples:
+/// ```rust
+/// use sugars::cnormalize;
+///
+/// # fn main() {
+/// let w = cnormalize![x in vec![10, 20, 15, 30].into_iter()];
+/// assert_eq!(vec![0.0, 0.5, 0.25, 1.0], w);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! cnormalize {
+    ($x:ident in $($tokens: tt)+) => {
+        $crate::cnormalize![$x; $x in $($tokens)+]
+    };
+
+    ($($tokens: tt)+) => {{
+        let mut values = $crate::c![$($tokens)+]
+            .map(::std::convert::Into::<f64>::into)
+            .collect::<::std::vec::Vec<f64>>();
+        let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+        let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        let range = max - min;
+        for value in &mut values {
+            *value = if range > 0.0 { (*value - min) / range } else { 0.0 };
+        }
+        values
+    }};
+}
+
+/// Build a [`HashMap`] from the position of each item of collection iterator comprehensions to
+/// the item.
+///
+/// The positions count only the produced items, so with an `if` clause the surviving items
+/// are numbered from `0` without gaps.
+///
+/// # Examples:
+/// ```rust
+/// use sugars::cenummap;
+///
+/// # fn main() {
+/// let w = cenummap![x * 10; x in 1..4];
+///
+/// assert_eq!(10, w[&0]);
+/// assert_eq!(20, w[&1]);
+/// assert_eq!(30, w[&2]);
+/// # }
+/// ```
+///
+/// [`HashMap`]: ::std::collections::HashMap
+#[macro_export]
+macro_rules! cenummap {
+    ($($tokens: tt)+) => {
+        $crate::c![$($tokens)+]
+            .enumerate()
+            .collect::<::std::collections::HashMap<usize, _>>()
+    };
+}
+
+/// Build [`Vec`] from collection iterator comprehensions, clamping each item into the
+/// `[lo, hi]` range.
+///
+/// The items must implement [`Ord`] and [`Copy`].
+///
+/// It also accepts the [`x in iter` shorthand](crate#shorthand-generator).
+///
+/// # Panics
+/// Panics if `lo` is greater than `hi`.
+///
+/// # Examples:
+/// ```rust
+/// use sugars::cclamp;
+///
+/// # fn main() {
+/// let w = cclamp!(0, 10; x in vec![-5, 3, 20].into_iter());
+/// assert_eq!(vec![0, 3, 10], w);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! cclamp {
+    ($lo:expr, $hi:expr; $x:ident in $($tokens: tt)+) => {
+        $crate::cclamp!($lo, $hi; $x; $x in $($tokens)+)
+    };
+
+    ($lo:expr, $hi:expr; $($tokens: tt)+) => {{
+        let (lo, hi) = ($lo, $hi);
+        $crate::c![$($tokens)+]
+            .map(|item| ::std::cmp::Ord::clamp(item, lo, hi))
+            .collect::<::std::vec::Vec<_>>()
+    }};
+}
+
+/// Clones the entries of a map whose key passes the predicate into a new [`HashMap`].
+///
+/// Used by the [`cfilter_keys`] macro.
+///
+/// [`HashMap`]: ::std::collections::HashMap
+/// [`cfilter_keys`]: crate::cfilter_keys
+#[doc(hidden)]
+pub fn filter_keys<K, V, S, F>(
+    map: &std::collections::HashMap<K, V, S>,
+    mut f: F,
+) -> std::collections::HashMap<K, V>
+where
+    K: Eq + std::hash::Hash + Clone,
+    V: Clone,
+    F: FnMut(&K) -> bool,
+{
+    map.iter()
+        .filter(|(k, _)| f(k))
+        .map(|(k, v)| (k.clone(), v.clone()))
+        .collect()
+}
+
+/// Clones the entries of a map whose value passes the predicate into a new [`HashMap`].
+///
+/// Used by the [`cfilter_values`] macro.
+///
+/// [`HashMap`]: ::std::collections::HashMap
+/// [`cfilter_values`]: crate::cfilter_values
+#[doc(hidden)]
+pub fn filter_values<K, V, S, F>(
+    map: &std::collections::HashMap<K, V, S>,
+    mut f: F,
+) -> std::collections::HashMap<K, V>
+where
+    K: Eq + std::hash::Hash + Clone,
+    V: Clone,
+    F: FnMut(&V) -> bool,
+{
+    map.iter()
+        .filter(|(_, v)| f(v))
+        .map(|(k, v)| (k.clone(), v.clone()))
+        .collect()
+}
+
+/// Build a [`HashMap`] with the entries of a map whose key passes the predicate.
+///
+/// The map is only borrowed, so the kept keys and values are cloned. The keys must implement
+/// [`Eq`], [`Hash`] and [`Clone`], and the values must implement [`Clone`].
+///
+/// # Examples:
+/// ```rust
+/// use sugars::{cfilter_keys, hmap};
+///
+/// # fn main() {
+/// let map = hmap! {1 => "one", 2 => "two", 3 => "three"};
+/// let w = cfilter_keys!(|k| *k > 1; map);
+///
+/// assert_eq!(hmap! {2 => "two", 3 => "three"}, w);
+/// # }
+/// ```
+///
+/// [`HashMap`]: ::std::collections::HashMap
+/// [`Hash`]: ::std::hash::Hash
+#[macro_export]
+macro_rules! cfilter_keys {
+    ($f:expr; $map:expr $(,)?) => {
+        $crate::filter_keys(&$map, $f)
+    };
+}
+
+/// Build a [`HashMap`] with the entries of a map whose value passes the predicate.
+///
+/// The map is only borrowed, so the kept keys and values are cloned. The keys must implement
+/// [`Eq`], [`Hash`] and [`Clone`], and the values must implement [`Clone`].
+///
+/// # Examples:
+/// ```rust
+/// use sugars::{cfilter_values, hmap};
+///
+/// # fn main() {
+/// let map = hmap! {"a" => 1, "b" => 20, "c" => 30};
+/// let w = cfilter_values!(|v| *v >= 20; map);
+///
+/// assert_eq!(hmap! {"b" => 20, "c" => 30}, w);
+/// # }
+/// ```
+///
+/// [`HashMap`]: ::std::collections::HashMap
+/// [`Hash`]: ::std::hash::Hash
+#[macro_export]
+macro_rules! cfilter_values {
+    ($f:expr; $map:expr $(,)?) => {
+        $crate::filter_values(&$map, $f)
+    };
+}
+
+/// Clones the entries of a map into a [`Vec`] sorted by key.
+///
+/// Used by the [`cmap_entries_sorted`] macro.
+///
+/// [`cmap_entries_sorted`]: crate::cmap_entries_sorted
+#[doc(hidden)]
+pub fn sorted_entries<'a, K, V, I>(entries: I) -> Vec<(K, V)>
+where
+    K: Ord + Clone + 'a,
+    V: Clone + 'a,
+    I: IntoIterator<Item = (&'a K, &'a V)>,
+{
+    let mut entries: Vec<(K, V)> = entries
+        .into_iter()
+        .map(|(k, v)| (k.clone(), v.clone()))
+        .collect();
+    entries.sort_by(|a, b| a.0.cmp(&b.0));
+    entries
+}
+
+/// Build a [`Vec`] of a map's entries sorted by key.
+///
+/// The map is only borrowed, so the keys and values are cloned. The keys must implement [`Ord`]
+/// and [`Clone`], and the values must implement [`Clone`]. This gives a deterministic order even
+/// for a [`HashMap`] source.
+///
+/// An optional `(key, value) => body` transform is applied to each entry after sorting.
+///
+/// # Examples:
+/// ```rust
+/// use sugars::{cmap_entries_sorted, hmap};
+///
+/// # fn main() {
+/// let map = hmap! {3 => "three", 1 => "one", 2 => "two"};
+///
+/// let w = cmap_entries_sorted![map];
+/// assert_eq!(vec![(1, "one"), (2, "two"), (3, "three")], w);
+///
+/// let w = cmap_entries_sorted![(k, v) => format!("{}={}", k, v); map];
+/// assert_eq!(vec!["1=one", "2=two", "3=three"], w);
+/// # }
+/// ```
+///
+/// [`Vec`]: ::std::vec::Vec
+/// [`HashMap`]: ::std::collections::HashMap
+#[macro_export]
+macro_rules! cmap_entries_sorted {
+    (($k:pat, $v:pat) => $e:expr; $map:expr $(,)?) => {
+        $crate::sorted_entries(&$map)
+            .into_iter()
+            .map(|($k, $v)| $e)
+            .collect::<::std::vec::Vec<_>>()
+    };
+    ($map:expr $(,)?) => {
+        $crate::sorted_entries(&$map)
+    };
+}
+
+/// Build a [`Vec`] applying a function to each window of the given size over collection
+/// iterator comprehensions.
+///
+/// The produced items are collected first, then the function is called with a slice of each
+/// window, in order. The output has `len - window + 1` items, so it is empty if the window is
+/// larger than the number of produced items.
+///
+/// It also accepts the [`x in iter` shorthand](crate#shorthand-generator).
+///
+/// # Panics
+/// Panics if the window size is zero.
+///
+/// # Examples:
+/// ```rust
+/// use sugars::crolling;
+///
+/// # fn main() {
+/// let w = crolling!(3, |w: &[i32]| *w.iter().max().unwrap(); x in vec![1, 5, 2, 4, 3].into_iter());
+/// assert_eq!(vec![5, 5, 4], w);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! crolling {
+    ($window:expr, $f:expr; $x:ident in $($tokens: tt)+) => {
+        $crate::crolling!($window, $f; $x; $x in $($tokens)+)
+    };
+
+    ($window:expr, $f:expr; $($tokens: tt)+) => {{
+        let window: usize = $window;
+        assert!(window > 0, "crolling!: window size must be greater than zero");
+        let items: ::std::vec::Vec<_> = $crate::c![$($tokens)+].collect();
+        items.windows(window).map($f).collect::<::std::vec::Vec<_>>()
+    }};
+}
+
+/// Build a [`Vec`] of samples by calling a function the given number of times, sleeping the
+/// given interval between each call.
+///
+/// There is no sleep before the first call nor after the last one, so collecting `count`
+/// samples takes at least `(count - 1) * interval`.
+///
+/// # Examples:
+/// ```rust
+/// use sugars::{csample_time, dur};
+///
+/// # fn main() {
+/// let mut reading = 0;
+/// let w = csample_time!(dur!(1 milli); || { reading += 10; reading }; 3);
+/// assert_eq!(vec![10, 20, 30], w);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! csample_time {
+    ($interval:expr; $f:expr; $count:expr $(,)?) => {{
+        let interval: ::std::time::Duration = $interval;
+        let count: usize = $count;
+        #[allow(unused_mut)]
+        let mut read = $f;
+        let mut samples = ::std::vec::Vec::with_capacity(count);
+        for i in 0..count {
+            if i > 0 {
+                ::std::thread::sleep(interval);
+            }
+            samples.push(read());
+        }
+        samples
+    }};
+}
+
+/// Complete a map with a default value for each of the listed keys it doesn't have yet.
+///
+/// The map is taken by value and returned with the missing keys inserted, each with a clone of
+/// the default. The values of the keys already present are kept. It works with any map with an
+/// `entry` API, like [`HashMap`] and [`BTreeMap`].
+///
+/// # Examples:
+/// ```rust
+/// use sugars::{cfill_defaults, hmap};
+///
+/// # fn main() {
+/// let config = hmap! {"a" => 1, "c" => 3};
+/// let w = cfill_defaults!(config, ["a", "b", "c"], 0);
+///
+/// assert_eq!(hmap! {"a" => 1, "b" => 0, "c" => 3}, w);
+/// # }
+/// ```
+///
+/// [`HashMap`]: ::std::collections::HashMap
+/// [`BTreeMap`]: ::std::collections::BTreeMap
+#[macro_export]
+macro_rules! cfill_defaults {
+    ($map:expr, [$($key:expr),* $(,)?], $default:expr $(,)?) => {{
+        #[allow(unused_mut)]
+        let mut map = $map;
+        #[allow(unused_variables)]
+        let default = $default;
+        $(
+            map.entry($key).or_insert_with(|| ::std::clone::Clone::clone(&default));
+        )*
+        map
+    }};
+}
+
+/// Build two column [`Vec`]s from collection iterator comprehensions producing pairs.
+///
+/// This is [`Iterator::unzip`] applied to the comprehension: the first items of the pairs go
+/// to the first [`Vec`] and the second items to the second one, keeping their positions aligned.
+///
+/// # Examples:
+/// ```rust
+/// use sugars::ccolumns;
+///
+/// # fn main() {
+/// let (xs, squares) = ccolumns![(x, x * x); x in 1..5, if x % 2 == 0];
+///
+/// assert_eq!(vec![2, 4], xs);
+/// assert_eq!(vec![4, 16], squares);
+/// # }
+/// ```
+///
+/// [`Vec`]: ::std::vec::Vec
+#[macro_export]
+macro_rules! ccolumns {
+    ($($tokens: tt)+) => {{
+        $crate::c![$($tokens)+].unzip::<_, _, ::std::vec::Vec<_>, ::std::vec::Vec<_>>()
+    }};
+}
+
+/// Build [`Vec`] from collection iterator comprehensions, evaluating them in parallel.
+///
+/// With the `rayon` feature enabled, the source is turned into a parallel iterator with
+/// `rayon`'s [`IntoParallelIterator`], so the expression and condition must be safe to share
+/// between threads. Without it, the source is consumed sequentially with [`IntoIterator`], like
+/// in [`cvec`]. In both cases the order of the produced items is preserved.
+///
+/// To build with and without the feature, the source must implement both
+/// [`IntoParallelIterator`] and [`IntoIterator`], like ranges and collections such as [`Vec`]
+/// do. Adapted std iterators, like the result of [`Iterator::map`], are not accepted.
+///
+/// ## Limitations
+///  * Only a single generator is supported, with an optional condition
+///
+/// # Examples:
+/// ```rust
+/// use sugars::cpar;
+///
+/// # fn main() {
+/// let w = cpar![x * x; x in 0..1000];
+/// let z = cpar![x; x in 0..1000, if x % 2 == 0];
+///
+/// assert_eq!(w[999], 998_001);
+/// assert_eq!(z[1], 2);
+/// # }
+/// ```
+///
+/// [`IntoParallelIterator`]: https://docs.rs/rayon/1/rayon/iter/trait.IntoParallelIterator.html
+/// [`cvec`]: crate::cvec
+#[cfg(feature = "rayon")]
+#[macro_export]
+macro_rules! cpar {
+    ($e:expr; $i:pat in $iter:expr) => {{
+        use $crate::rayon::prelude::*;
+        $crate::rayon::iter::IntoParallelIterator::into_par_iter($iter)
+            .map(|$i| $e)
+            .collect::<::std::vec::Vec<_>>()
+    }};
+
+    ($e:expr; $i:pat in $iter:expr, if $cond:expr) => {{
+        use $crate::rayon::prelude::*;
+        $crate::rayon::iter::IntoParallelIterator::into_par_iter($iter)
+            .filter(|$i| $cond)
+            .map(|$i| $e)
+            .collect::<::std::vec::Vec<_>>()
+    }};
+}
+
+/// Build [`Vec`] from collection iterator comprehensions, evaluating them in parallel.
+///
+/// With the `rayon` feature enabled, the source is turned into a parallel iterator with
+/// `rayon`'s [`IntoParallelIterator`], so the expression and condition must be safe to share
+/// between threads. Without it, the source is consumed sequentially with [`IntoIterator`], like
+/// in [`cvec`]. In both cases the order of the produced items is preserved.
+///
+/// To build with and without the feature, the source must implement both
+/// [`IntoParallelIterator`] and [`IntoIterator`], like ranges and collections such as [`Vec`]
+/// do. Adapted std iterators, like the result of [`Iterator::map`], are not accepted.
+///
+/// ## Limitations
+///  * Only a single generator is supported, with an optional condition
+///
+/// # Examples:
+/// ```rust
+/// use sugars::cpar;
+///
+/// # fn main() {
+/// let w = cpar![x * x; x in 0..1000];
+/// let z = cpar![x; x in 0..1000, if x % 2 == 0];
+///
+/// assert_eq!(w[999], 998_001);
+/// assert_eq!(z[1], 2);
+/// # }
+/// ```
+///
+/// [`IntoParallelIterator`]: https://docs.rs/rayon/1/rayon/iter/trait.IntoParallelIterator.html
+/// [`cvec`]: crate::cvec
+#[cfg(not(feature = "rayon"))]
+#[macro_export]
+macro_rules! cpar {
+    ($e:expr; $i:pat in $iter:expr) => {
+        ::std::iter::IntoIterator::into_iter($iter)
+            .map(|$i| $e)
+            .collect::<::std::vec::Vec<_>>()
+    };
+
+    ($e:expr; $i:pat in $iter:expr, if $cond:expr) => {
+        ::std::iter::IntoIterator::into_iter($iter)
+            .filter(|$i| $cond)
+            .map(|$i| $e)
+            .collect::<::std::vec::Vec<_>>()
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+    #[test]
+    fn c_no_conditional() {
+        let expected = vec![2, 4, 6, 8];
+        let test: Vec<_> = c![x*2; x in 1..5].collect();
+
+        assert_eq!(expected, test);
+    }
+
+    #[test]
+    fn c_with_conditional() {
+        let expected = vec![0, 2, 4, 6, 8];
+        let test: Vec<_> = c![x; x in 0..10, if x%2 == 0].collect();
+
+        assert_eq!(expected, test);
+    }
+
+    #[test]
+    fn c_2_nested_no_conditional() {
+        let expected = vec![1, 2, 3, 4, 5, 6, 7, 8, 9];
+        let nested = vec![vec![1, 2, 3], vec![4, 5, 6], vec![7, 8, 9]];
+        let test: Vec<_> = c![x; y in nested.into_iter(), x in y.into_iter()].collect();
+
+        assert_eq!(expected, test);
+    }
+
+    #[test]
+    fn c_2_nested_with_conditional() {
+        let expected = vec![2, 4, 6, 8];
+        let nested = vec![vec![1, 2, 3], vec![4, 5, 6], vec![7, 8, 9]];
+        let test: Vec<_> =
+            c![x; y in nested.into_iter(), x in y.into_iter(), if x % 2 == 0].collect();
+
+        assert_eq!(expected, test);
+    }
+
+    #[test]
+    fn c_3_nested_no_conditional() {
+        let expected = vec![
+            (1, 1, 1),
+            (1, 1, 2),
+            (1, 1, 3),
+            (1, 1, 4),
+            (1, 2, 2),
+            (1, 2, 3),
+            (1, 2, 4),
+            (1, 3, 3),
+            (1, 3, 4),
+            (1, 4, 4),
+            (2, 2, 2),
+            (2, 2, 3),
+            (2, 2, 4),
+            (2, 3, 3),
+            (2, 3, 4),
+            (2, 4, 4),
+            (3, 3, 3),
+            (3, 3, 4),
+            (3, 4, 4),
+            (4, 4, 4),
+        ];
+        let n: i32 = 4;
+        let test: Vec<_> = c![(x, y, z); x in 1..=n, y in x..=n, z in y..=n].collect();
+
+        assert_eq!(expected, test);
+    }
+
+    #[test]
+    fn c_3_nested_with_conditional() {
+        let expected = vec![(3, 4, 5), (6, 8, 10)];
+        let n: i32 = 10;
+        let test: Vec<_> =
+            c![(x,y, z); x in 1..=n, y in x..=n, z in y..=n, if x.pow(2) + y.pow(2) == z.pow(2)]
+                .collect();
+
+        assert_eq!(expected, test);
+    }
+
+    #[test]
+    fn c_4_nested_no_conditional() {
+        let expected: Vec<_> = (0..16)
+            .map(|n| (n >> 3, (n >> 2) & 1, (n >> 1) & 1, n & 1))
+            .collect();
+        let test: Vec<_> = c![(a, b, c, d); a in 0..2, b in 0..2, c in 0..2, d in 0..2].collect();
+
+        assert_eq!(expected, test);
+    }
+
+    #[test]
+    fn c_4_nested_with_conditional() {
+        let expected = vec![
+            (0, 1, 2, 3),
+            (0, 1, 2, 4),
+            (0, 1, 3, 4),
+            (0, 2, 3, 4),
+            (1, 2, 3, 4),
+        ];
+        let test: Vec<_> = c![
+            (a, b, c, d);
+            a in 0..5, b in a + 1..5, c in b + 1..5, d in c + 1..5, if a + b + c + d <= 10
+        ]
+        .collect();
+
+        assert_eq!(expected, test);
+    }
+
+    #[test]
+    fn c_5_nested() {
+        let nested = vec![
+            vec![vec![vec![vec![1, 2], vec![3]], vec![vec![4]]]],
+            vec![vec![vec![vec![5, 6]]]],
+        ];
+        let test: Vec<_> = c![
+            x;
+            a in nested.into_iter(),
+            b in a.into_iter(),
+            c in b.into_iter(),
+            d in c.into_iter(),
+            x in d.into_iter()
+        ]
+        .collect();
+        assert_eq!(vec![1, 2, 3, 4, 5, 6], test);
+
+        let test = cvec![
+            (a, b, c, d, e);
+            a in 0..3, b in 0..3, c in 0..3, d in 0..3, e in 0..3, if a + b + c + d + e == 10
+        ];
+        assert_eq!(vec![(2, 2, 2, 2, 2)], test);
+    }
+
+    #[test]
+    fn cvec_basic_no_conditional() {
+        let expected = vec![2, 4, 6, 8];
         let test = cvec![x*2; x in 1..5];
 
         assert_eq!(expected, test);
     }
 
     #[test]
-    fn cvec_basic_with_conditional() {
-        let expected = vec![0, 2, 4, 6, 8];
-        let test = cvec![x; x in 0..10, if x%2 == 0];
+    fn cvec_preallocated() {
+        let test = cvec![x * 2; x in 0..100_000];
+
+        assert_eq!(100_000, test.len());
+        assert!(test.capacity() >= test.len());
+        assert!(test.capacity() < 2 * test.len());
+        assert_eq!((0..100_000).map(|x| x * 2).collect::<Vec<_>>(), test);
+    }
+
+    #[test]
+    fn cvec_basic_with_conditional() {
+        let expected = vec![0, 2, 4, 6, 8];
+        let test = cvec![x; x in 0..10, if x%2 == 0];
+
+        assert_eq!(expected, test);
+    }
+
+    #[test]
+    fn cvec_2_nested_no_conditional() {
+        let expected = vec![1, 2, 3, 4, 5, 6, 7, 8, 9];
+        let nested = vec![vec![1, 2, 3], vec![4, 5, 6], vec![7, 8, 9]];
+        let test = cvec![x; y in nested.into_iter(), x in y.into_iter()];
+
+        assert_eq!(expected, test);
+    }
+
+    #[test]
+    fn cvec_2_nested_with_conditional() {
+        let expected = vec![2, 4, 6, 8];
+        let nested = vec![vec![1, 2, 3], vec![4, 5, 6], vec![7, 8, 9]];
+        let test = cvec![x; y in nested.into_iter(), x in y.into_iter(), if x % 2 == 0];
+
+        assert_eq!(expected, test);
+    }
+
+    #[test]
+    fn cvec_3_nested_no_conditional() {
+        let expected = vec![
+            (1, 1, 1),
+            (1, 1, 2),
+            (1, 1, 3),
+            (1, 1, 4),
+            (1, 2, 2),
+            (1, 2, 3),
+            (1, 2, 4),
+            (1, 3, 3),
+            (1, 3, 4),
+            (1, 4, 4),
+            (2, 2, 2),
+            (2, 2, 3),
+            (2, 2, 4),
+            (2, 3, 3),
+            (2, 3, 4),
+            (2, 4, 4),
+            (3, 3, 3),
+            (3, 3, 4),
+            (3, 4, 4),
+            (4, 4, 4),
+        ];
+        let n: i32 = 4;
+        let test = cvec![(x, y, z); x in 1..=n, y in x..=n, z in y..=n];
+
+        assert_eq!(expected, test);
+    }
+
+    #[test]
+    fn cvec_just_in_3_nested_with_conditional() {
+        let expected = vec![(3, 4, 5), (6, 8, 10)];
+        let n: i32 = 10;
+        let test =
+            cvec![(x,y, z); x in 1..=n, y in x..=n, z in y..=n, if x.pow(2) + y.pow(2) == z.pow(2)];
+
+        assert_eq!(expected, test);
+    }
+
+    #[test]
+    fn cmap_zip() {
+        let test = cmap!(zip vec!["a", "b"], vec![1, 2]);
+
+        assert_eq!(2, test.len());
+        assert_eq!(1, test["a"]);
+        assert_eq!(2, test["b"]);
+    }
+
+    #[test]
+    fn cmap_zip_shorter() {
+        let test = cmap!(zip 0..10, "xyz".chars());
+
+        assert_eq!(3, test.len());
+        assert_eq!('z', test[&2]);
+    }
+
+    #[test]
+    fn cmap_zip_with_conditional() {
+        let keys = ["a", "bb", "ccc", "dddd"];
+        let test = cmap!(zip keys.iter().copied(), 1..=4, if |k, v| k.len() == *v && v % 2 == 0);
+
+        let mut expected = HashMap::new();
+        expected.insert("bb", 2);
+        expected.insert("dddd", 4);
+        assert_eq!(expected, test);
+    }
+
+    #[test]
+    fn cmap_just_in_no_conditional() {
+        let a = 10;
+        let mut expected = HashMap::new();
+        for i in 1..10 {
+            expected.insert(i, i + a);
+        }
+        let test = cmap! {x => x+a; x in 1..10};
+
+        assert_eq!(expected, test);
+    }
+
+    #[test]
+    fn cmap_just_in_with_conditional() {
+        let a = 10;
+        let mut expected = HashMap::new();
+        for i in 1..10 {
+            if i % 2 == 0 {
+                expected.insert(i, i + a);
+            }
+        }
+        let test = cmap! {x => x+a; x in 1..10, if x%2==0};
+
+        assert_eq!(expected, test);
+    }
+
+    #[test]
+    fn cset_just_in_no_conditional() {
+        let mut expected = HashSet::new();
+        for i in 1..10 {
+            expected.insert(i);
+        }
+        let test = cset! {x; x in 1..10};
+
+        assert_eq!(expected, test);
+    }
+
+    #[test]
+    fn cset_just_in_with_conditional() {
+        let mut expected = HashSet::new();
+        for i in 1..10 {
+            if i % 2 == 0 {
+                expected.insert(i);
+            }
+        }
+        let test = cset! {x; x in 1..10, if x%2==0};
+
+        assert_eq!(expected, test);
+    }
+
+    #[test]
+    fn cbtmap_just_in_no_conditional() {
+        let a = 10;
+        let mut expected = BTreeMap::new();
+        for i in 1..10 {
+            expected.insert(i, i + a);
+        }
+        let test = cbtmap! {x => x+a; x in 1..10};
+
+        assert_eq!(expected, test);
+    }
+
+    #[test]
+    fn cbtmap_just_in_with_conditional() {
+        let a = 10;
+        let mut expected = BTreeMap::new();
+        for i in 1..10 {
+            if i % 2 == 0 {
+                expected.insert(i, i + a);
+            }
+        }
+        let test = cbtmap! {x => x+a; x in 1..10, if x%2==0};
+
+        assert_eq!(expected, test);
+    }
+
+    #[test]
+    fn cbtset_just_in_no_conditional() {
+        let mut expected = BTreeSet::new();
+        for i in 1..10 {
+            expected.insert(i);
+        }
+        let test = cbtset! {x; x in 1..10};
+
+        assert_eq!(expected, test);
+    }
+
+    #[test]
+    fn cbtset_just_in_with_conditional() {
+        let mut expected = BTreeSet::new();
+        for i in 1..10 {
+            if i % 2 == 0 {
+                expected.insert(i);
+            }
+        }
+        let test = cbtset! {x; x in 1..10, if x%2==0};
+
+        assert_eq!(expected, test);
+    }
+
+    #[test]
+    fn cstring_no_conditional() {
+        let test = cstring![c.to_ascii_uppercase(); c in "hello".chars()];
+
+        assert_eq!("HELLO", test);
+    }
+
+    #[test]
+    fn cstring_with_conditional() {
+        let expected: String = "  some text\twith spaces "
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .collect();
+        let test = cstring![c; c in "  some text\twith spaces ".chars(), if !c.is_whitespace()];
+
+        assert_eq!(expected, test);
+        assert_eq!("sometextwithspaces", test);
+    }
+
+    #[test]
+    fn cstring_from_str() {
+        let test = cstring![w; w in vec!["a", "bc", "def"].into_iter()];
+
+        assert_eq!("abcdef", test);
+    }
+
+    #[test]
+    fn cresult_ok() {
+        let test: Result<Vec<i32>, std::num::ParseIntError> =
+            cresult![s.parse(); s in vec!["4", "-2", "7"].into_iter()];
+
+        assert_eq!(Ok(vec![4, -2, 7]), test);
+    }
+
+    #[test]
+    fn cresult_first_err() {
+        let mut seen = Vec::new();
+        let test = cresult![
+            if x == 3 || x == 5 { Err(x) } else { seen.push(x); Ok(x * 2) };
+            x in 0..10
+        ];
+
+        assert_eq!(Err(3), test);
+        assert_eq!(vec![0, 1, 2], seen);
+    }
+
+    #[test]
+    fn cresult_with_conditional() {
+        let input = vec!["1", "", "x", "3"];
+        let test = cresult![s.parse::<i32>().map_err(|_| s); s in input.clone().into_iter(), if !s.is_empty()];
+        assert_eq!(Err("x"), test);
+
+        let test =
+            cresult![s.parse::<i32>(); s in input.into_iter(), if !s.is_empty() && *s != "x"];
+        assert_eq!(Ok(vec![1, 3]), test);
+    }
+
+    #[test]
+    fn cdense_pairs() {
+        let pairs = vec![(1, 10), (3, 30), (4, 40)];
+        let test = cdense!(6, 0; (i, v) in pairs.into_iter());
+
+        assert_eq!(vec![0, 10, 0, 30, 40, 0], test);
+    }
+
+    #[test]
+    fn cdense_with_conditional() {
+        let expected = vec![String::from("0"), String::new(), String::from("2")];
+        let test = cdense!(3, String::new(); x => x.to_string(); x in 0..3, if *x != 1);
+
+        assert_eq!(expected, test);
+    }
+
+    #[test]
+    #[should_panic]
+    fn cdense_out_of_range() {
+        let _ = cdense!(2, 0; (i, v) in vec![(2, 1)].into_iter());
+    }
+
+    #[test]
+    fn cmovavg_shorthand() {
+        let expected = vec![2.0, 3.0, 4.0, 6.0, 8.0];
+        let test = cmovavg!(3; x in vec![2, 4, 6, 8, 10].into_iter());
+
+        assert_eq!(expected, test);
+    }
+
+    #[test]
+    fn cmovavg_with_conditional() {
+        let expected = vec![0.0, 1.0, 3.0, 5.0];
+        let test = cmovavg!(2; f64::from(x); x in 0..8, if x % 2 == 0);
+
+        assert_eq!(expected, test);
+    }
+
+    #[test]
+    fn cmovavg_window_one() {
+        let expected = vec![1.5, 2.5];
+        let test = cmovavg!(1; x in vec![1.5, 2.5].into_iter());
+
+        assert_eq!(expected, test);
+    }
+
+    #[test]
+    fn coverlay() {
+        let expected = vec!["a", "B", "c"];
+        let test = coverlay!(
+            vec!["a", "b", "c"].into_iter(),
+            vec![None, Some("B"), None].into_iter()
+        );
+
+        assert_eq!(expected, test);
+    }
+
+    #[test]
+    fn coverlay_truncates() {
+        let test = coverlay!(0..10, vec![Some(10), None].into_iter());
+        assert_eq!(vec![10, 1], test);
+
+        let test = coverlay!(0..2, c![Some(x * 10); x in 0..5]);
+        assert_eq!(vec![0, 10], test);
+    }
+
+    #[test]
+    fn cdedup_by() {
+        struct Item {
+            id: u32,
+            name: &'static str,
+        }
+
+        let items = vec![
+            Item { id: 1, name: "a" },
+            Item { id: 1, name: "b" },
+            Item { id: 2, name: "c" },
+            Item { id: 1, name: "d" },
+            Item { id: 1, name: "e" },
+        ];
+        let test = cdedup_by!(|a, b| a.id == b.id; x in items.into_iter());
+        let names: Vec<_> = test.iter().map(|item| item.name).collect();
+
+        assert_eq!(vec!["a", "c", "d"], names);
+    }
+
+    #[test]
+    fn cdedup_by_with_conditional() {
+        let expected = vec![1, 3, 1];
+        let test =
+            cdedup_by!(|a, b| a == b; x; x in vec![1, 1, 2, 3, 3, 1].into_iter(), if *x != 2);
+
+        assert_eq!(expected, test);
+    }
+
+    #[test]
+    fn caccum() {
+        let (test, sum) = caccum!(0, |acc, x| acc + x; x in 0..10);
+
+        assert_eq!((0..10).collect::<Vec<_>>(), test);
+        assert_eq!(45, sum);
+    }
+
+    #[test]
+    fn caccum_with_conditional() {
+        let (test, max) = caccum!(None, |acc: Option<i32>, x| acc.max(Some(*x)); x * 3; x in 0..10, if x % 4 == 0);
+
+        assert_eq!(vec![0, 12, 24], test);
+        assert_eq!(Some(24), max);
+    }
+
+    #[test]
+    fn cpositions() {
+        let expected = vec![0, 3, 6, 9];
+        let test = cpositions![x in 10..20, if x % 3 == 1];
+
+        assert_eq!(expected, test);
+    }
+
+    #[test]
+    fn cpositions_no_match() {
+        let test = cpositions![x in 0..10, if x > 10];
+
+        assert!(test.is_empty());
+    }
+
+    #[test]
+    fn cpar() {
+        let expected = cvec![x * 2; x in 0..10_000];
+        let test = cpar![x * 2; x in 0..10_000];
+
+        assert_eq!(expected, test);
+    }
+
+    #[test]
+    fn cpar_with_conditional() {
+        let expected = cvec![x.to_string(); x in 0..10_000, if x % 7 == 0];
+        let test = cpar![x.to_string(); x in 0..10_000, if x % 7 == 0];
+
+        assert_eq!(expected, test);
+    }
+
+    #[test]
+    fn cpar_vec_source() {
+        let source: Vec<u64> = (0..10_000).collect();
+        let expected: Vec<_> = source.iter().map(|x| x * x).collect();
+        let test = cpar![x * x; x in source.clone()];
+
+        assert_eq!(expected, test);
+
+        let expected: Vec<_> = source
+            .iter()
+            .filter(|x| *x % 3 == 0)
+            .map(|x| x + 1)
+            .collect();
+        let test = cpar![x + 1; x in source, if *x % 3 == 0];
+
+        assert_eq!(expected, test);
+    }
+
+    #[test]
+    fn csplit_first() {
+        let (head, tail) = csplit_first![x in vec![1, 2, 0, 3, 0, 4].into_iter(), if *x == 0];
+
+        assert_eq!(vec![1, 2], head);
+        assert_eq!(vec![0, 3, 0, 4], tail);
+    }
+
+    #[test]
+    fn csplit_first_no_match() {
+        let (head, tail) = csplit_first![x in 0..5, if *x > 10];
+
+        assert_eq!(vec![0, 1, 2, 3, 4], head);
+        assert!(tail.is_empty());
+    }
+
+    #[test]
+    fn csplit_first_at_start() {
+        let (head, tail) = csplit_first![x in vec![5, 1, 2].into_iter(), if *x > 3];
+
+        assert!(head.is_empty());
+        assert_eq!(vec![5, 1, 2], tail);
+    }
+
+    #[test]
+    fn cidx_filter() {
+        let expected = vec![(0, 5), (4, 2)];
+        let test =
+            cidx_filter![(i, x) in vec![5, 3, -2, 7, 2].into_iter(), if i % 2 == 0 && *x > 0];
+
+        assert_eq!(expected, test);
+    }
+
+    #[test]
+    fn cidx_filter_by_index() {
+        let expected = vec![(2, 'c'), (3, 'd')];
+        let test = cidx_filter![(i, _c) in "abcdef".chars(), if (2..4).contains(i)];
+
+        assert_eq!(expected, test);
+    }
+
+    #[test]
+    fn cintersperse() {
+        let empty: Vec<i32> = cintersperse!(0; x; x in 0..0);
+        assert!(empty.is_empty());
+
+        assert_eq!(vec![1], cintersperse!(0; x; x in 1..2));
+        assert_eq!(vec![2, 0, 4, 0, 6], cintersperse!(0; x * 2; x in 1..=3));
+    }
+
+    #[test]
+    fn cintersperse_with_conditional() {
+        let expected = vec!["0", "-", "2", "-", "4"];
+        let test = cintersperse!("-"; x; x in vec!["0", "1", "2", "3", "4"].into_iter(), if x.parse::<i32>().unwrap() % 2 == 0);
+
+        assert_eq!(expected, test);
+    }
+
+    #[test]
+    fn crle() {
+        let expected = vec![('a', 3), ('b', 1), ('a', 2)];
+        let test = crle![c in "aaabaa".chars()];
+
+        assert_eq!(expected, test);
+    }
+
+    #[test]
+    fn crle_single_and_empty() {
+        assert_eq!(vec![(7, 1)], crle![x in vec![7].into_iter()]);
+
+        let test: Vec<(i32, usize)> = crle![x in Vec::new().into_iter()];
+        assert!(test.is_empty());
+    }
+
+    #[test]
+    fn crle_with_conditional() {
+        let expected = vec![(true, 2), (false, 2)];
+        let test = crle![x < 5; x in 0..10, if x % 3 == 0];
+
+        assert_eq!(expected, test);
+    }
+
+    #[test]
+    fn cdistinct_counts() {
+        let items = vec![('a', 1), ('a', 1), ('a', 2), ('b', 3), ('b', 3), ('c', 4)];
+        let mut expected = HashMap::new();
+        expected.insert('a', 2);
+        expected.insert('b', 1);
+        expected.insert('c', 1);
+        let test = cdistinct_counts! {k => v; (k, v) in items.into_iter()};
+
+        assert_eq!(expected, test);
+    }
+
+    #[test]
+    fn cdistinct_counts_with_conditional() {
+        let mut expected = HashMap::new();
+        expected.insert(0, 4);
+        expected.insert(1, 3);
+        let test = cdistinct_counts! {x % 2 => x % 5; x in 0..12, if x % 3 != 0};
+
+        assert_eq!(expected, test);
+    }
+
+    #[test]
+    fn cwinsum() {
+        let input = vec![4, -2, 7, 1, 0, 3];
+
+        assert_eq!(input.clone(), cwinsum!(1; x in input.clone().into_iter()));
+        assert_eq!(
+            vec![2, 5, 8, 1, 3],
+            cwinsum!(2; x in input.clone().into_iter())
+        );
+        assert_eq!(vec![10, 6, 11], cwinsum!(4; x in input.into_iter()));
+    }
+
+    #[test]
+    fn cwinsum_larger_than_input() {
+        let test = cwinsum!(5; x in 0..4);
+
+        assert!(test.is_empty());
+    }
+
+    #[test]
+    fn cwinsum_with_conditional() {
+        let expected = vec![2.0, 4.0];
+        let test = cwinsum!(2; x as f64 / 2.0; x in 0..6, if x % 2 != 0);
+
+        assert_eq!(expected, test);
+    }
+
+    #[test]
+    fn creduce() {
+        let items = vec![("a", 1), ("b", 4), ("a", 5), ("a", 2), ("b", 3)];
+
+        let max = creduce!(|acc, v| acc.max(v); k => v; (k, v) in items.clone().into_iter());
+        assert_eq!(2, max.len());
+        assert_eq!(5, max["a"]);
+        assert_eq!(4, max["b"]);
+
+        let sum = creduce!(|acc, v| acc + v; k => v; (k, v) in items.into_iter());
+        assert_eq!(8, sum["a"]);
+        assert_eq!(7, sum["b"]);
+    }
+
+    #[test]
+    fn creduce_single_value_per_key() {
+        let expected = cmap! {x => x * 10; x in 0..5};
+        let test = creduce!(|_, _| unreachable!(); x => x * 10; x in 0..5);
+
+        assert_eq!(expected, test);
+    }
+
+    #[test]
+    fn ctopn() {
+        let expected = vec![99, 98, 97];
+        let test = ctopn!(3; (x * 37) % 100; x in 0..100);
+
+        assert_eq!(expected, test);
+    }
+
+    #[test]
+    fn ctopn_fewer_than_n() {
+        assert_eq!(vec![3, 2, 1], ctopn!(5; x in vec![2, 3, 1].into_iter()));
+        assert!(ctopn!(0; x in 0..10).is_empty());
+    }
+
+    #[test]
+    fn ctopn_by() {
+        struct Player {
+            name: &'static str,
+            score: u32,
+        }
+
+        let players = vec![
+            Player {
+                name: "a",
+                score: 10,
+            },
+            Player {
+                name: "b",
+                score: 40,
+            },
+            Player {
+                name: "c",
+                score: 20,
+            },
+            Player {
+                name: "d",
+                score: 30,
+            },
+        ];
+        let test = ctopn_by!(3, |p| p.score; p in players.into_iter());
+        let names: Vec<_> = test.iter().map(|p| p.name).collect();
+
+        assert_eq!(vec!["b", "d", "c"], names);
+    }
+
+    #[test]
+    fn cnested_map() {
+        let rows = vec![("a", 1, 'x'), ("a", 2, 'y'), ("b", 1, 'z'), ("a", 1, 'w')];
+        let test = cnested_map! {outer, inner => value; (outer, inner, value) in rows.into_iter()};
+
+        assert_eq!(2, test.len());
+        assert_eq!(2, test["a"].len());
+        assert_eq!('w', test["a"][&1]);
+        assert_eq!('y', test["a"][&2]);
+        assert_eq!('z', test["b"][&1]);
+        assert!(!test["b"].contains_key(&2));
+    }
+
+    #[test]
+    fn cvec_log_errs() {
+        let input = vec!["1", "a", "2", "b", "c", "3"];
+        let mut errors = Vec::new();
+        let test = cvec_log_errs!(|e| errors.push(e); x.parse::<u8>(); x in input.into_iter());
+
+        assert_eq!(vec![1, 2, 3], test);
+        assert_eq!(3, errors.len());
+    }
+
+    #[test]
+    fn cgroup_sorted() {
+        let items = vec![(1, 'c'), (2, 'z'), (1, 'a'), (2, 'x'), (1, 'b'), (3, 'q')];
+        let test = cgroup_sorted! {k => v; (k, v) in items.into_iter()};
+
+        assert_eq!(3, test.len());
+        assert_eq!(vec!['a', 'b', 'c'], test[&1]);
+        assert_eq!(vec!['x', 'z'], test[&2]);
+        assert_eq!(vec!['q'], test[&3]);
+    }
+
+    #[test]
+    fn crepeat_each() {
+        assert_eq!(vec![1, 2, 3], crepeat_each!(1; x in 1..=3));
+        assert_eq!(
+            vec!["a", "a", "a", "b", "b", "b"],
+            crepeat_each!(3; x in vec!["a", "b"].into_iter())
+        );
+        assert_eq!(
+            vec![0, 0, 4, 4],
+            crepeat_each!(2; x * x; x in 0..3, if x % 2 == 0)
+        );
+        assert!(crepeat_each!(0; x in 0..3).is_empty());
+        assert!(crepeat_each!(4; x in Vec::<String>::new().into_iter()).is_empty());
+    }
+
+    #[test]
+    fn csymdiff() {
+        use std::collections::HashSet;
+
+        let a: HashSet<_> = vec!["a", "b", "c"].into_iter().collect();
+        let b: HashSet<_> = vec!["b", "c", "d", "e"].into_iter().collect();
+        let expected: HashSet<_> = vec!["a", "d", "e"].into_iter().collect();
+        assert_eq!(expected, csymdiff!(a, b));
+        assert_eq!(expected, csymdiff!(&b, &a));
+
+        let c: HashSet<_> = vec!["x"].into_iter().collect();
+        let expected: HashSet<_> = vec!["a", "b", "c", "x"].into_iter().collect();
+        assert_eq!(expected, csymdiff!(a, c));
+        assert!(csymdiff!(a, a).is_empty());
+    }
+
+    #[test]
+    fn cbtsymdiff() {
+        use std::collections::BTreeSet;
+
+        let a: BTreeSet<_> = (1..=5).collect();
+        let b: BTreeSet<_> = (4..=8).collect();
+        assert_eq!(
+            vec![1, 2, 3, 6, 7, 8],
+            cbtsymdiff!(a, b).into_iter().collect::<Vec<_>>()
+        );
+
+        let c: BTreeSet<_> = (10..=11).collect();
+        assert_eq!(
+            vec![1, 2, 3, 4, 5, 10, 11],
+            cbtsymdiff!(a, c).into_iter().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn cdiff() {
+        assert_eq!(vec![2, 3, 4], cdiff![x in vec![1, 3, 6, 10].into_iter()]);
+        assert_eq!(vec![-5, -1, -4], cdiff![x in vec![10, 5, 4, 0].into_iter()]);
+        assert_eq!(
+            vec![1.5, 2.5],
+            cdiff![x as f64 / 2.0; x in vec![0, 3, 8].into_iter()]
+        );
+
+        assert!(cdiff![x in Vec::<i32>::new().into_iter()].is_empty());
+        assert!(cdiff![x in vec![7].into_iter()].is_empty());
+    }
+
+    #[test]
+    fn cindex() {
+        let test = cindex![x in vec![5, 3, 5, 1, 3, 3].into_iter()];
+
+        assert_eq!(3, test.len());
+        assert_eq!(0, test[&5]);
+        assert_eq!(1, test[&3]);
+        assert_eq!(3, test[&1]);
+
+        let test = cindex![x % 3; x in 1..10];
+        assert_eq!(0, test[&1]);
+        assert_eq!(1, test[&2]);
+        assert_eq!(2, test[&0]);
+    }
+
+    #[test]
+    fn cnormalize() {
+        let test = cnormalize![x in vec![3u8, 7, 5, 11, 9].into_iter()];
+        assert_eq!(vec![0.0, 0.5, 0.25, 1.0, 0.75], test);
+
+        let test = cnormalize![-x; x in vec![0.0f32, 2.0, 1.0].into_iter()];
+        assert_eq!(vec![1.0, 0.0, 0.5], test);
+
+        let test = cnormalize![x in vec![4, 4, 4].into_iter()];
+        assert_eq!(vec![0.0, 0.0, 0.0], test);
+
+        assert!(cnormalize![x in Vec::<i32>::new().into_iter()].is_empty());
+    }
+
+    #[test]
+    fn cenummap() {
+        let input = vec!["a", "b", "c", "d"];
+        let test = cenummap![v; v in input.clone().into_iter()];
+
+        assert_eq!(input.len(), test.len());
+        for (i, v) in input.iter().enumerate() {
+            assert_eq!(v, &test[&i]);
+        }
+    }
+
+    #[test]
+    fn cenummap_with_conditional() {
+        let test = cenummap![x; x in 0..10, if *x % 3 == 0];
+
+        assert_eq!(4, test.len());
+        assert_eq!(0, test[&0]);
+        assert_eq!(3, test[&1]);
+        assert_eq!(6, test[&2]);
+        assert_eq!(9, test[&3]);
+    }
+
+    #[test]
+    fn cclamp() {
+        let test = cclamp!(-2, 2; x in vec![-10, -2, 0, 1, 2, 7].into_iter());
+        assert_eq!(vec![-2, -2, 0, 1, 2, 2], test);
+
+        let test = cclamp!('b', 'd'; c in "abcde".chars());
+        assert_eq!(vec!['b', 'b', 'c', 'd', 'd'], test);
+
+        let test = cclamp!(5, 5; x * 2; x in 0..5);
+        assert_eq!(vec![5; 5], test);
+    }
+
+    #[test]
+    fn cfilter_keys() {
+        let map: HashMap<_, _> = (0..6).map(|i| (i, i * 10)).collect();
+        let test = cfilter_keys!(|k| k % 2 == 0; map);
+
+        let expected: HashMap<_, _> = vec![(0, 0), (2, 20), (4, 40)].into_iter().collect();
+        assert_eq!(expected, test);
+        assert_eq!(6, map.len());
+    }
+
+    #[test]
+    fn cfilter_values() {
+        let map: HashMap<_, _> = vec![("a", 5), ("b", 15), ("c", 10), ("d", 25)]
+            .into_iter()
+            .collect();
+        let test = cfilter_values!(|v| *v >= 10; map);
 
+        let expected: HashMap<_, _> = vec![("b", 15), ("c", 10), ("d", 25)].into_iter().collect();
         assert_eq!(expected, test);
+        assert!(cfilter_values!(|v| *v > 100; map).is_empty());
     }
 
     #[test]
-    fn cvec_2_nested_no_conditional() {
-        let expected = vec![1, 2, 3, 4, 5, 6, 7, 8, 9];
-        let nested = vec![vec![1, 2, 3], vec![4, 5, 6], vec![7, 8, 9]];
-        let test = cvec![x; y in nested.into_iter(), x in y.into_iter()];
+    fn cmap_entries_sorted() {
+        let map: HashMap<_, _> = (0..20).rev().map(|x| (x, x * 10)).collect();
+        let expected: Vec<_> = (0..20).map(|x| (x, x * 10)).collect();
+        assert_eq!(expected, cmap_entries_sorted![map]);
+
+        let map = HashMap::from([("b", 2), ("c", 3), ("a", 1)]);
+        let w = cmap_entries_sorted![(k, v) => format!("{}{}", k, v); map];
+        assert_eq!(vec!["a1", "b2", "c3"], w);
+        assert_eq!(3, map.len());
+    }
+
+    #[test]
+    fn c_two_conditionals() {
+        let expected: Vec<_> = (0..100)
+            .filter(|x| x % 2 == 0)
+            .filter(|x| x % 3 == 0)
+            .collect();
+        let test: Vec<_> = c![x; x in 0..100, if x % 2 == 0, if x % 3 == 0].collect();
 
         assert_eq!(expected, test);
     }
 
     #[test]
-    fn cvec_2_nested_with_conditional() {
-        let expected = vec![2, 4, 6, 8];
-        let nested = vec![vec![1, 2, 3], vec![4, 5, 6], vec![7, 8, 9]];
-        let test = cvec![x; y in nested.into_iter(), x in y.into_iter(), if x % 2 == 0];
+    fn c_three_conditionals() {
+        let expected: Vec<_> = (0..100)
+            .filter(|x| x % 2 == 0)
+            .filter(|x| x % 3 == 0)
+            .filter(|x| *x > 50)
+            .map(|x| x * 10)
+            .collect();
+        let test: Vec<_> =
+            c![x * 10; x in 0..100, if x % 2 == 0, if x % 3 == 0, if *x > 50].collect();
 
         assert_eq!(expected, test);
     }
 
     #[test]
-    fn cvec_3_nested_no_conditional() {
-        let expected = vec![
-            (1, 1, 1),
-            (1, 1, 2),
-            (1, 1, 3),
-            (1, 1, 4),
-            (1, 2, 2),
-            (1, 2, 3),
-            (1, 2, 4),
-            (1, 3, 3),
-            (1, 3, 4),
-            (1, 4, 4),
-            (2, 2, 2),
-            (2, 2, 3),
-            (2, 2, 4),
-            (2, 3, 3),
-            (2, 3, 4),
-            (2, 4, 4),
-            (3, 3, 3),
-            (3, 3, 4),
-            (3, 4, 4),
-            (4, 4, 4),
-        ];
-        let n: i32 = 4;
-        let test = cvec![(x, y, z); x in 1..=n, y in x..=n, z in y..=n];
+    fn c_nested_multiple_conditionals() {
+        let expected: Vec<_> = (0..10)
+            .flat_map(|x| (0..10).map(move |y| (x, y)))
+            .filter(|(x, y)| x < y)
+            .filter(|(x, y)| (x + y) % 3 == 0)
+            .filter(|(_, y)| y % 2 == 1)
+            .collect();
+        let test: Vec<_> =
+            c![(x, y); x in 0..10, y in 0..10, if x < y, if (x + y) % 3 == 0, if y % 2 == 1]
+                .collect();
 
         assert_eq!(expected, test);
     }
 
     #[test]
-    fn cvec_just_in_3_nested_with_conditional() {
-        let expected = vec![(3, 4, 5), (6, 8, 10)];
-        let n: i32 = 10;
-        let test =
-            cvec![(x,y, z); x in 1..=n, y in x..=n, z in y..=n, if x.pow(2) + y.pow(2) == z.pow(2)];
+    fn c_if_let() {
+        let items = vec![Some(1), None, Some(3), None, Some(5)];
+        let expected = vec![1, 3, 5];
+        let test: Vec<_> = c![v; item in items.into_iter(), if let Some(v) = item].collect();
 
         assert_eq!(expected, test);
     }
 
     #[test]
-    fn cmap_just_in_no_conditional() {
-        let a = 10;
-        let mut expected = HashMap::new();
-        for i in 1..10 {
-            expected.insert(i, i + a);
-        }
-        let test = cmap! {x => x+a; x in 1..10};
+    fn c_if_let_output_expression() {
+        let items = vec![Ok(2), Err("bad"), Ok(4)];
+        let expected = vec![(0, 20), (2, 40)];
+        let test: Vec<_> =
+            c![(i, v * 10); (i, item) in items.into_iter().enumerate(), if let Ok(v) = item]
+                .collect();
 
         assert_eq!(expected, test);
     }
 
     #[test]
-    fn cmap_just_in_with_conditional() {
-        let a = 10;
-        let mut expected = HashMap::new();
-        for i in 1..10 {
-            if i % 2 == 0 {
-                expected.insert(i, i + a);
-            }
-        }
-        let test = cmap! {x => x+a; x in 1..10, if x%2==0};
+    fn c_nested_if_let() {
+        let rows = vec![vec![Some(1), None], vec![None, Some(4), Some(6)]];
+        let expected = vec![1, 4, 6];
+        let test: Vec<_> =
+            c![v; row in rows.into_iter(), item in row.into_iter(), if let Some(v) = item]
+                .collect();
 
         assert_eq!(expected, test);
     }
 
     #[test]
-    fn cset_just_in_no_conditional() {
-        let mut expected = HashSet::new();
-        for i in 1..10 {
-            expected.insert(i);
-        }
-        let test = cset! {x; x in 1..10};
+    fn c_let_binding() {
+        let expected: Vec<_> = (0..10).map(|x| x * x).filter(|y| y % 3 == 0).collect();
+        let test: Vec<_> = c![y; x in 0..10, let y = x * x, if y % 3 == 0].collect();
 
         assert_eq!(expected, test);
     }
 
     #[test]
-    fn cset_just_in_with_conditional() {
-        let mut expected = HashSet::new();
-        for i in 1..10 {
-            if i % 2 == 0 {
-                expected.insert(i);
-            }
-        }
-        let test = cset! {x; x in 1..10, if x%2==0};
+    fn c_let_binding_evaluated_once() {
+        let calls = std::cell::Cell::new(0);
+        let expensive = |x: i32| {
+            calls.set(calls.get() + 1);
+            x - 5
+        };
+        let test: Vec<_> = c![(x, y * 2); x in 0..10, let y = expensive(x), if y > 0].collect();
+
+        assert_eq!(vec![(6, 2), (7, 4), (8, 6), (9, 8)], test);
+        assert_eq!(10, calls.get());
+    }
+
+    #[test]
+    fn c_multiple_let_bindings() {
+        let words = vec!["a", "bb", "ccc", "dddd"];
+        let expected = vec![(String::from("bb"), 4), (String::from("dddd"), 16)];
+        let test: Vec<_> = c![
+            (s, sq); w in words.into_iter(), let s = w.to_string(), let sq = s.len() * s.len(), if sq % 2 == 0
+        ]
+        .collect();
 
         assert_eq!(expected, test);
     }
 
     #[test]
-    fn cbtmap_just_in_no_conditional() {
-        let a = 10;
-        let mut expected = BTreeMap::new();
-        for i in 1..10 {
-            expected.insert(i, i + a);
-        }
-        let test = cbtmap! {x => x+a; x in 1..10};
+    fn c_let_between_generators() {
+        let expected: Vec<_> = (1..4)
+            .flat_map(|x| (0..x * 2).map(move |y| (x, y)))
+            .filter(|(x, y)| y % x == 0)
+            .collect();
+        let test: Vec<_> = c![(x, y); x in 1..4, let n = x * 2, y in 0..n, if y % x == 0].collect();
 
         assert_eq!(expected, test);
     }
 
     #[test]
-    fn cbtmap_just_in_with_conditional() {
-        let a = 10;
-        let mut expected = BTreeMap::new();
-        for i in 1..10 {
-            if i % 2 == 0 {
-                expected.insert(i, i + a);
-            }
-        }
-        let test = cbtmap! {x => x+a; x in 1..10, if x%2==0};
+    fn c_let_destructuring() {
+        let pairs = vec![(1, 2), (3, 4), (5, 6)];
+        let test: Vec<_> =
+            c![s; p in pairs.into_iter(), let (a, b) = p, let s = a + b, if s > 3].collect();
+
+        assert_eq!(vec![7, 11], test);
+    }
+
+    #[test]
+    fn crolling_max() {
+        let input = vec![4, -2, 7, 1, 0, 3];
+        let test =
+            crolling!(3, |w: &[i32]| *w.iter().max().unwrap(); x in input.clone().into_iter());
+
+        assert_eq!(vec![7, 7, 7, 3], test);
+        assert_eq!(input.len() - 3 + 1, test.len());
+    }
+
+    #[test]
+    fn crolling_custom_aggregation() {
+        let words = vec!["a", "bb", "ccc", "dddd"];
+        let test =
+            crolling!(2, |w: &[String]| w.concat(); s.to_uppercase(); s in words.into_iter());
+
+        assert_eq!(vec!["ABB", "BBCCC", "CCCDDDD"], test);
+    }
+
+    #[test]
+    fn crolling_with_conditional() {
+        let test = crolling!(2, |w: &[i32]| w[1] - w[0]; x * x; x in 0..10, if x % 3 == 0);
+
+        assert_eq!(vec![9, 27, 45], test);
+    }
+
+    #[test]
+    fn crolling_larger_than_input() {
+        let test = crolling!(5, |w: &[i32]| w.len(); x in 0..4);
+
+        assert!(test.is_empty());
+    }
+
+    #[test]
+    fn c_enumerate() {
+        let v = ["a", "b", "c"];
+        let expected = vec![(0, "a"), (1, "b"), (2, "c")];
+        let test: Vec<_> = c![(i, *x); (i, x) in enumerate v.iter()].collect();
 
         assert_eq!(expected, test);
     }
 
     #[test]
-    fn cbtset_just_in_no_conditional() {
-        let mut expected = BTreeSet::new();
-        for i in 1..10 {
-            expected.insert(i);
-        }
-        let test = cbtset! {x; x in 1..10};
+    fn c_enumerate_with_conditional() {
+        let v = [10, 15, 20, 25, 30];
+        let expected: Vec<_> = v.iter().enumerate().filter(|(_, x)| *x % 10 == 0).collect();
+        let test: Vec<_> = c![(i, x); (i, x) in enumerate v.iter(), if *x % 10 == 0].collect();
 
         assert_eq!(expected, test);
+        assert_eq!(
+            vec![0, 2, 4],
+            c![i; (i, x) in enumerate v.iter(), if *x % 10 == 0].collect::<Vec<_>>()
+        );
     }
 
     #[test]
-    fn cbtset_just_in_with_conditional() {
-        let mut expected = BTreeSet::new();
-        for i in 1..10 {
-            if i % 2 == 0 {
-                expected.insert(i);
-            }
-        }
-        let test = cbtset! {x; x in 1..10, if x%2==0};
+    fn c_enumerate_nested() {
+        let rows = [vec!['a', 'b'], vec!['c', 'd', 'e']];
+        let expected = vec![
+            (0, 0, 'a'),
+            (0, 1, 'b'),
+            (1, 0, 'c'),
+            (1, 1, 'd'),
+            (1, 2, 'e'),
+        ];
+        let test: Vec<_> = c![
+            (r, c, x); (r, row) in enumerate rows.iter(), (c, x) in enumerate row.iter().copied()
+        ]
+        .collect();
+
+        assert_eq!(expected, test);
+    }
+
+    #[test]
+    fn csample_time() {
+        use std::time::{Duration, Instant};
+
+        let interval = Duration::from_millis(5);
+        let start = Instant::now();
+        let mut reads = 0;
+        let test = csample_time!(interval; || { reads += 1; reads * 2 }; 4);
+
+        assert!(start.elapsed() >= interval * 3);
+        assert_eq!(vec![2, 4, 6, 8], test);
+        assert_eq!(4, reads);
+    }
+
+    #[test]
+    fn csample_time_no_samples() {
+        let test: Vec<i32> = csample_time!(crate::dur!(1 sec); || unreachable!(); 0);
+
+        assert!(test.is_empty());
+    }
+
+    #[test]
+    fn c_zip() {
+        let xs = [1, 2, 3];
+        let ys = [10, 20, 30];
+        let expected: Vec<_> = xs.iter().zip(ys.iter()).map(|(a, b)| a + b).collect();
+        let test: Vec<_> = c![a + b; a in xs.iter(), zip b in ys.iter()].collect();
+
+        assert_eq!(expected, test);
+        assert_eq!(vec![11, 22, 33], test);
+    }
+
+    #[test]
+    fn c_zip_contrast_cartesian() {
+        let zipped: Vec<_> = c![(a, b); a in 0..3, zip b in 'a'..='c'].collect();
+        let product: Vec<_> = c![(a, b); a in 0..3, b in 'a'..='c'].collect();
+
+        assert_eq!(vec![(0, 'a'), (1, 'b'), (2, 'c')], zipped);
+        assert_eq!(9, product.len());
+        assert!(zipped.iter().all(|pair| product.contains(pair)));
+    }
+
+    #[test]
+    fn c_zip_shorter_length() {
+        let test: Vec<_> = c![(a, b); a in 0..10, zip b in 0..3].collect();
+        assert_eq!(vec![(0, 0), (1, 1), (2, 2)], test);
 
+        let test: Vec<_> = c![(a, b); a in 0..2, zip b in 0..30].collect();
+        assert_eq!(vec![(0, 0), (1, 1)], test);
+    }
+
+    #[test]
+    fn c_zip_with_conditional() {
+        let test: Vec<_> =
+            c![a * b; a in 0..6, zip b in 10..16, if a % 2 == 0, if *b > 10].collect();
+
+        assert_eq!(vec![24, 56], test);
+    }
+
+    #[test]
+    fn c_zip_chained_and_nested() {
+        let zipped3: Vec<_> = c![(a, b, c); a in 0..3, zip b in 3..6, zip c in 6..9].collect();
+        assert_eq!(vec![(0, 3, 6), (1, 4, 7), (2, 5, 8)], zipped3);
+
+        let nested: Vec<_> =
+            c![(x, a, b); x in 0..2, a in 0..2, zip b in enumerate "xy".chars()].collect();
+        let expected = vec![
+            (0, 0, (0, 'x')),
+            (0, 1, (1, 'y')),
+            (1, 0, (0, 'x')),
+            (1, 1, (1, 'y')),
+        ];
+        assert_eq!(expected, nested);
+    }
+
+    #[test]
+    fn cfill_defaults() {
+        let mut map = HashMap::new();
+        map.insert(String::from("host"), String::from("example.com"));
+        map.insert(String::from("extra"), String::from("kept"));
+
+        let test = cfill_defaults!(
+            map,
+            [String::from("host"), String::from("port")],
+            String::from("unset")
+        );
+
+        assert_eq!(3, test.len());
+        assert_eq!("example.com", test["host"]);
+        assert_eq!("unset", test["port"]);
+        assert_eq!("kept", test["extra"]);
+    }
+
+    #[test]
+    fn cfill_defaults_btreemap() {
+        let map: BTreeMap<_, _> = vec![(2, vec![20])].into_iter().collect();
+        let test = cfill_defaults!(map, [1, 2, 3], Vec::new());
+
+        let expected: BTreeMap<_, _> = vec![(1, vec![]), (2, vec![20]), (3, vec![])]
+            .into_iter()
+            .collect();
         assert_eq!(expected, test);
     }
+
+    #[test]
+    fn cfill_defaults_no_keys() {
+        let map: HashMap<&str, i32> = HashMap::new();
+        let test = cfill_defaults!(map, [], 0);
+
+        assert!(test.is_empty());
+    }
+
+    #[test]
+    fn ccolumns() {
+        let words = vec!["a", "bb", "ccc"];
+        let (upper, lens) = ccolumns![(w.to_uppercase(), w.len()); w in words.into_iter()];
+
+        assert_eq!(vec!["A", "BB", "CCC"], upper);
+        assert_eq!(vec![1, 2, 3], lens);
+    }
+
+    #[test]
+    fn ccolumns_aligned_with_conditional() {
+        let (xs, ys) = ccolumns![(x, x * 3); x in 0..20, if x % 4 == 1];
+
+        assert_eq!(xs.len(), ys.len());
+        assert_eq!(vec![1, 5, 9, 13, 17], xs);
+        assert!(xs.iter().zip(ys.iter()).all(|(x, y)| x * 3 == *y));
+    }
 }
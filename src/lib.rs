@@ -40,7 +40,7 @@
 //!     * [**time**]: Print out the time it took to execute a given expression in seconds.
 //!
 //!  1. Returns a tuple if multiple parameters are given.
-//!  2. Accepted time patterns are: `min`, `sec`, `nano`, `micro` and `milli`.
+//!  2. Accepted time patterns are: `week`, `day`, `hour`, `min`, `sec`, `nano`, `micro` and `milli`.
 //!
 //! ## Examples
 //! ### `std::collections`
@@ -146,6 +146,15 @@
 //! cmap! {x => x*2; x in 1..10, if x % 2 == 0};
 //! ```
 //!
+//! #### Shorthand generator
+//! Comprehensions that only work on the produced items, like **`cmovavg`**, **`crle`**,
+//! **`ctopn`** or **`measure_each`**, also accept `x in iter` as a shorthand for `x; x in iter`:
+//! ```rust
+//! use sugars::crle;
+//!
+//! assert_eq!(crle![c in "aab".chars()], crle![c; c in "aab".chars()]);
+//! ```
+//!
 //! ### Time/Duration:
 //! Usage of **`dur`** and **`sleep`**:
 //! ```rust
@@ -171,8 +180,13 @@
 //! let x = time!( 100 + 20 );
 //! ```
 //!
+//! ## Cargo features
+//!
+//!  * `rayon`: Makes `cpar!` evaluate comprehensions in parallel using [rayon](https://crates.io/crates/rayon).
+//!  * `tokio`: Enables `sleep_async!`, which sleeps without blocking using [tokio](https://crates.io/crates/tokio).
+//!
 //! ## Minimal Viable Rust Version
-//! This software requires Rust version equal or above 1.39.0.
+//! This software requires Rust version equal or above 1.70.0.
 //!
 //! ## LICENSE
 //! This software is licensed under the [MIT Public License](./LICENSE).
@@ -226,3 +240,20 @@ mod comprehension;
 mod hash;
 mod pointer;
 mod times;
+
+#[cfg(feature = "rayon")]
+#[doc(hidden)]
+pub use rayon;
+#[cfg(feature = "tokio")]
+#[doc(hidden)]
+pub use tokio;
+
+#[doc(hidden)]
+pub use comprehension::{filter_keys, filter_values, sorted_entries, top_n_by};
+#[doc(hidden)]
+pub use pointer::{is_default, IntoInner, TryUnwrap};
+#[doc(hidden)]
+pub use times::{block_with, coarse_now, humanize, jitter, poll_deadline, start_instant, Profile};
+
+pub use pointer::{ArcCow, Lazy};
+pub use times::{ProfileReport, ScopeTimer, TickCounter};
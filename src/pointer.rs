@@ -64,6 +64,10 @@ macro_rules! rc {
 /// - Borrowed: Uses [`Cow::Borrowed`] in the passed expression
 /// - Owned: Uses [`Cow::Owned`] in the passed expression
 ///
+/// The `borrow slice` and `own slice` forms build a `Cow<'_, [T]>` from anything that can be
+/// sliced or turned into a [`Vec`], like vectors and arrays. A slice reference passed to the
+/// auto form is borrowed too.
+///
 /// # Examples
 ///
 /// ```
@@ -75,6 +79,10 @@ macro_rules! rc {
 /// let auto = cow!(&s);
 /// let borrowed = cow!(borrow s);
 /// let owned: Cow<'_, String> = cow!(own String::from("Owned"));
+///
+/// let v = vec![1, 2, 3];
+/// let borrowed_slice = cow!(borrow slice v);
+/// let owned_slice = cow!(own slice [4, 5, 6]);
 /// ```
 ///
 /// ```
@@ -97,9 +105,15 @@ macro_rules! cow {
     ($e:expr) => {
         ::std::borrow::Cow::from($e)
     };
+    (borrow slice $e:expr) => {
+        ::std::borrow::Cow::<[_]>::Borrowed(&$e[..])
+    };
     (borrow $e:ident) => {
         ::std::borrow::Cow::Borrowed(&$e)
     };
+    (own slice $e:expr) => {
+        ::std::borrow::Cow::<[_]>::Owned(::std::vec::Vec::from($e))
+    };
     (own $e:expr) => {
         ::std::borrow::Cow::Owned($e)
     };
@@ -236,180 +250,1923 @@ macro_rules! rwlock {
         ::std::sync::RwLock::new($e)
     };
     ($e:expr,) => {
-        $crate::mutex!($e)
+        $crate::rwlock!($e)
     };
     ($($e:expr),+ $(,)?) => {
-        ($($crate::mutex!($e)),+,)
+        ($($crate::rwlock!($e)),+,)
     };
 }
 
-#[cfg(test)]
-mod tests {
+/// Create a new boxed array `Box<[T; N]>` with all elements set to the given value.
+///
+/// Unlike `Box::new([value; N])`, which builds the array on the stack before moving it to the
+/// heap, this allocates the elements directly on the heap, so it is safe to use with arrays too
+/// big to fit the stack. The value must implement [`Clone`] and `N` must be a constant
+/// expression.
+///
+/// # Example
+/// ```
+/// use sugars::box_array;
+/// # fn main() {
+/// let array: Box<[u8; 1_000_000]> = box_array!(0u8; 1_000_000);
+/// assert_eq!(1_000_000, array.len());
+/// # }
+/// ```
+#[macro_export]
+macro_rules! box_array {
+    ($e:expr; $n:expr) => {{
+        let slice = ::std::vec![$e; $n].into_boxed_slice();
+        let array: ::std::boxed::Box<[_; $n]> = match ::std::convert::TryFrom::try_from(slice) {
+            Ok(array) => array,
+            Err(_) => unreachable!("box_array!: slice length always matches the array length"),
+        };
+        array
+    }};
+}
 
-    #[test]
-    fn boxed() {
-        assert_eq!(Box::new(10), boxed!(10));
-        assert_eq!(Box::new(Some("String")), boxed!(Some("String")));
-    }
+/// Get the strong and weak reference counts of a [`Rc`], as a `(strong, weak)` tuple.
+///
+/// # Example
+/// ```
+/// use std::rc::Rc;
+/// use sugars::{rc, refcount};
+/// # fn main() {
+/// let a = rc!(10);
+/// let _b = Rc::clone(&a);
+/// let _weak = Rc::downgrade(&a);
+///
+/// assert_eq!((2, 1), refcount!(a));
+/// # }
+/// ```
+///
+/// [`Rc`]: ::std::rc::Rc
+#[macro_export]
+macro_rules! refcount {
+    ($e:expr) => {
+        (
+            ::std::rc::Rc::strong_count(&$e),
+            ::std::rc::Rc::weak_count(&$e),
+        )
+    };
+}
 
-    #[test]
-    fn boxed_trailing_comma() {
-        assert_eq!(Box::new(10), boxed!(10,));
-        assert_eq!(Box::new(Some("String")), boxed!(Some("String"),));
-    }
+/// Get the strong and weak reference counts of an [`Arc`], as a `(strong, weak)` tuple.
+///
+/// # Example
+/// ```
+/// use std::sync::Arc;
+/// use sugars::{arc, arc_refcount};
+/// # fn main() {
+/// let a = arc!(10);
+/// let _b = Arc::clone(&a);
+/// let _weak = Arc::downgrade(&a);
+///
+/// assert_eq!((2, 1), arc_refcount!(a));
+/// # }
+/// ```
+///
+/// [`Arc`]: ::std::sync::Arc
+#[macro_export]
+macro_rules! arc_refcount {
+    ($e:expr) => {
+        (
+            ::std::sync::Arc::strong_count(&$e),
+            ::std::sync::Arc::weak_count(&$e),
+        )
+    };
+}
 
-    #[test]
-    fn boxed_tuples() {
-        let expected1 = (Box::new(10), Box::new(11));
-        let expected2 = (Box::new(Some("String")), Box::new(Some("other_str")));
-        assert_eq!(expected1, boxed!(10, 11));
-        assert_eq!(expected2, boxed!(Some("String"), Some("other_str")));
-    }
+/// Types that wrap a value which can be extracted by consuming the wrapper.
+///
+/// Used by the [`into_inner`] macro to dispatch on the wrapper type.
+///
+/// [`into_inner`]: crate::into_inner
+#[doc(hidden)]
+pub trait IntoInner {
+    type Inner;
 
-    #[test]
-    fn rc() {
-        use std::rc::Rc;
-        assert_eq!(Rc::new(10), rc!(10));
-        assert_eq!(Rc::new(Some("String")), rc!(Some("String")));
-    }
+    fn into_inner(self) -> Self::Inner;
+}
 
-    #[test]
-    fn rc_trailing_comma() {
-        use std::rc::Rc;
-        assert_eq!(Rc::new(10), rc!(10,));
-        assert_eq!(Rc::new(Some("String")), rc!(Some("String"),));
-    }
+impl<T> IntoInner for ::std::cell::Cell<T> {
+    type Inner = T;
 
-    #[test]
-    fn rc_tuples() {
-        use std::rc::Rc;
-        let expected1 = (Rc::new(10), Rc::new(11));
-        let expected2 = (Rc::new(Some("String")), Rc::new(Some("other_str")));
-        assert_eq!(expected1, rc!(10, 11));
-        assert_eq!(expected2, rc!(Some("String"), Some("other_str")));
+    fn into_inner(self) -> T {
+        ::std::cell::Cell::into_inner(self)
     }
+}
 
-    #[test]
-    fn cow() {
-        use std::borrow::Cow;
-
-        #[derive(Clone, PartialEq, Debug)]
-        struct Test(i32);
-
-        let test1 = String::from("Hello Cow");
-        let expected: Cow<str> = Cow::Borrowed(&test1);
-        let test: Cow<str> = cow!(&test1);
-
-        assert_eq!(expected, test);
-
-        let test2 = Test(10);
-        let expected: Cow<Test> = Cow::Borrowed(&test2);
-        let test = cow!(borrow test2);
-        assert_eq!(expected, test);
-
-        let expected: Cow<str> = Cow::Owned(String::from("Hello Cow"));
-        let test: Cow<str> = cow!(own String::from("Hello Cow"));
-
-        assert_eq!(expected, test);
+impl<T> IntoInner for ::std::cell::RefCell<T> {
+    type Inner = T;
 
-        let expected: Cow<Test> = Cow::Owned(Test(10));
-        let test = cow!(own Test(10));
-        assert_eq!(expected, test);
+    fn into_inner(self) -> T {
+        ::std::cell::RefCell::into_inner(self)
     }
+}
 
-    #[test]
-    fn cell() {
-        use std::cell::Cell;
-        assert_eq!(Cell::new(10), cell!(10));
-        assert_eq!(Cell::new(Some("String")), cell!(Some("String")));
-    }
+impl<T> IntoInner for ::std::sync::Mutex<T> {
+    type Inner = T;
 
-    #[test]
-    fn cell_trailing_comma() {
-        use std::cell::Cell;
-        assert_eq!(Cell::new(10), cell!(10,));
-        assert_eq!(Cell::new(Some("String")), cell!(Some("String"),));
+    fn into_inner(self) -> T {
+        ::std::sync::Mutex::into_inner(self).expect("into_inner!: the Mutex is poisoned")
     }
+}
 
-    #[test]
-    fn cell_tuples() {
-        use std::cell::Cell;
-        let expected1 = (Cell::new(10), Cell::new(11));
-        let expected2 = (Cell::new(Some("String")), Cell::new(Some("other_str")));
-        assert_eq!(expected1, cell!(10, 11));
-        assert_eq!(expected2, cell!(Some("String"), Some("other_str")));
-    }
+impl<T> IntoInner for ::std::sync::RwLock<T> {
+    type Inner = T;
 
-    #[test]
-    fn refcell() {
-        use std::cell::RefCell;
-        assert_eq!(RefCell::new(10), refcell!(10));
-        assert_eq!(RefCell::new(Some("String")), refcell!(Some("String")));
+    fn into_inner(self) -> T {
+        ::std::sync::RwLock::into_inner(self).expect("into_inner!: the RwLock is poisoned")
     }
+}
 
-    #[test]
-    fn refcell_trailing_comma() {
-        use std::cell::RefCell;
-        assert_eq!(RefCell::new(10), refcell!(10,));
-        assert_eq!(RefCell::new(Some("String")), refcell!(Some("String"),));
-    }
+/// Extract the inner value of a [`Cell`], [`RefCell`], [`Mutex`] or [`RwLock`], consuming it.
+///
+/// It is also able to create tuples if given more than one parameter.
+///
+/// # Panics
+/// Panics if a [`Mutex`] or [`RwLock`] is poisoned.
+///
+/// # Example
+/// ```
+/// use sugars::{into_inner, mutex, refcell};
+/// # fn main() {
+/// let m = mutex!(vec![1, 2]);
+/// m.lock().unwrap().push(3);
+/// assert_eq!(vec![1, 2, 3], into_inner!(m));
+///
+/// let (a, b) = into_inner!(refcell!(1), mutex!("a"));
+/// assert_eq!((1, "a"), (a, b));
+/// # }
+/// ```
+///
+/// [`Cell`]: ::std::cell::Cell
+/// [`RefCell`]: ::std::cell::RefCell
+/// [`Mutex`]: ::std::sync::Mutex
+/// [`RwLock`]: ::std::sync::RwLock
+#[macro_export]
+macro_rules! into_inner {
+    ($e:expr) => {
+        $crate::IntoInner::into_inner($e)
+    };
+    ($e:expr,) => {
+        $crate::into_inner!($e)
+    };
+    ($($e:expr),+ $(,)?) => {
+        ($($crate::into_inner!($e)),+,)
+    };
+}
 
-    #[test]
-    fn refcell_tuples() {
-        use std::cell::RefCell;
-        let expected1 = (RefCell::new(10), RefCell::new(11));
-        let expected2 = (
-            RefCell::new(Some("String")),
-            RefCell::new(Some("other_str")),
-        );
-        assert_eq!(expected1, refcell!(10, 11));
-        assert_eq!(expected2, refcell!(Some("String"), Some("other_str")));
-    }
+/// Shared pointers that can give back ownership of their value when uniquely owned.
+///
+/// Used by the [`try_unwrap`] macro to dispatch on the pointer type.
+///
+/// [`try_unwrap`]: crate::try_unwrap
+#[doc(hidden)]
+pub trait TryUnwrap: Sized {
+    type Inner;
 
-    #[test]
-    fn arc() {
-        use std::sync::Arc;
-        assert_eq!(Arc::new(10), arc!(10));
-        assert_eq!(Arc::new(Some("String")), arc!(Some("String")));
-    }
+    fn try_unwrap(self) -> Result<Self::Inner, Self>;
+}
 
-    #[test]
-    fn arc_trailing_comma() {
-        use std::sync::Arc;
-        assert_eq!(Arc::new(10), arc!(10,));
-        assert_eq!(Arc::new(Some("String")), arc!(Some("String"),));
-    }
+impl<T> TryUnwrap for ::std::rc::Rc<T> {
+    type Inner = T;
 
-    #[test]
-    fn arc_tuples() {
-        use std::sync::Arc;
-        let expected1 = (Arc::new(10), Arc::new(11));
-        let expected2 = (Arc::new(Some("String")), Arc::new(Some("other_str")));
-        assert_eq!(expected1, arc!(10, 11));
-        assert_eq!(expected2, arc!(Some("String"), Some("other_str")));
+    fn try_unwrap(self) -> Result<T, Self> {
+        ::std::rc::Rc::try_unwrap(self)
     }
+}
 
-    #[test]
-    fn mutex() {
-        use std::sync::Mutex;
-
-        let mutex_expected = Mutex::new(Some("String"));
-        let mutex_test = mutex!(Some("String"));
+impl<T> TryUnwrap for ::std::sync::Arc<T> {
+    type Inner = T;
 
-        let expected = mutex_expected.lock().unwrap();
-        let test = mutex_test.lock().unwrap();
-        assert_eq!(expected.is_some(), test.is_some());
+    fn try_unwrap(self) -> Result<T, Self> {
+        ::std::sync::Arc::try_unwrap(self)
     }
+}
 
-    #[test]
-    fn rwlock() {
-        use std::sync::RwLock;
+/// Reclaim the inner value of a [`Rc`] or [`Arc`] if it is uniquely owned.
+///
+/// Returns `Ok` with the inner value if there is exactly one strong reference, otherwise
+/// returns `Err` with the same pointer that was passed in.
+///
+/// # Example
+/// ```
+/// use std::rc::Rc;
+/// use sugars::{rc, try_unwrap};
+/// # fn main() {
+/// let a = rc!(10);
+/// let b = Rc::clone(&a);
+///
+/// let a = try_unwrap!(a).unwrap_err();
+/// drop(b);
+/// assert_eq!(Ok(10), try_unwrap!(a));
+/// # }
+/// ```
+///
+/// [`Rc`]: ::std::rc::Rc
+/// [`Arc`]: ::std::sync::Arc
+#[macro_export]
+macro_rules! try_unwrap {
+    ($e:expr) => {
+        $crate::TryUnwrap::try_unwrap($e)
+    };
+}
 
-        let rwlk_expected = RwLock::new(Some("String"));
-        let rwlk_test = rwlock!(Some("String"));
+/// Replace the value of a [`RefCell`] with the result of a function applied to it.
+///
+/// The function receives a mutable reference to the current value and returns the new value.
+/// It is a thin wrapper over [`RefCell::replace_with`], discarding the old value.
+///
+/// # Panics
+/// Panics if the value is currently borrowed.
+///
+/// # Example
+/// ```
+/// use sugars::{refcell, replace_with};
+/// # fn main() {
+/// let cell = refcell!(5);
+/// replace_with!(cell, |old| *old * 2);
+/// assert_eq!(10, *cell.borrow());
+/// # }
+/// ```
+///
+/// [`RefCell`]: ::std::cell::RefCell
+/// [`RefCell::replace_with`]: ::std::cell::RefCell::replace_with
+#[macro_export]
+macro_rules! replace_with {
+    ($cell:expr, $f:expr $(,)?) => {{
+        let _ = ::std::cell::RefCell::replace_with(&$cell, $f);
+    }};
+}
 
-        let expected = rwlk_expected.read().unwrap();
-        let test = rwlk_test.read().unwrap();
-        assert_eq!(expected.is_some(), test.is_some());
+/// Box the value inside an [`Option`], turning an `Option<T>` into an `Option<Box<T>>`.
+///
+/// # Example
+/// ```
+/// use sugars::box_option;
+/// # fn main() {
+/// assert_eq!(Some(Box::new(10)), box_option!(Some(10)));
+/// assert_eq!(None::<Box<i32>>, box_option!(None::<i32>));
+/// # }
+/// ```
+#[macro_export]
+macro_rules! box_option {
+    ($e:expr) => {
+        ::std::option::Option::map($e, ::std::boxed::Box::new)
+    };
+}
+
+/// Box the `Ok` value of a [`Result`], turning a `Result<T, E>` into a `Result<Box<T>, E>`.
+///
+/// # Example
+/// ```
+/// use sugars::box_result;
+/// # fn main() {
+/// assert_eq!(Ok(Box::new(10)), box_result!(Ok::<_, ()>(10)));
+/// assert_eq!(Err("error"), box_result!(Err::<i32, _>("error")));
+/// # }
+/// ```
+#[macro_export]
+macro_rules! box_result {
+    ($e:expr) => {
+        ::std::result::Result::map($e, ::std::boxed::Box::new)
+    };
+}
+
+/// Update the value of a [`Cell`] with the result of a function applied to it, returning the
+/// new value.
+///
+/// It works for [`Copy`] types, like the unstable `Cell::update`.
+///
+/// # Example
+/// ```
+/// use sugars::{cell, cell_update};
+/// # fn main() {
+/// let counter = cell!(0);
+/// cell_update!(counter, |v| v + 1);
+/// assert_eq!(2, cell_update!(counter, |v| v + 1));
+/// # }
+/// ```
+///
+/// [`Cell`]: ::std::cell::Cell
+#[macro_export]
+macro_rules! cell_update {
+    ($cell:expr, $f:expr $(,)?) => {{
+        fn update<T: Copy, F: FnOnce(T) -> T>(cell: &::std::cell::Cell<T>, f: F) -> T {
+            let new = f(cell.get());
+            cell.set(new);
+            new
+        }
+        update(&$cell, $f)
+    }};
+}
+
+/// Lock a [`Mutex`] only for the duration of a function call, returning the function result.
+///
+/// The function receives a mutable reference to the locked value, and the lock guard is
+/// dropped right after it returns, which makes the scope of the lock explicit and short.
+///
+/// # Panics
+/// Panics if the [`Mutex`] is poisoned.
+///
+/// # Example
+/// ```
+/// use sugars::{mutex, with_lock};
+/// # fn main() {
+/// let m = mutex!(Vec::new());
+/// let len = with_lock!(m, |v| {
+///     v.push(1);
+///     v.len()
+/// });
+///
+/// assert_eq!(1, len);
+/// # }
+/// ```
+///
+/// [`Mutex`]: ::std::sync::Mutex
+#[macro_export]
+macro_rules! with_lock {
+    ($mutex:expr, $f:expr $(,)?) => {{
+        fn with_lock<T: ?Sized, R, F: FnOnce(&mut T) -> R>(
+            mutex: &::std::sync::Mutex<T>,
+            f: F,
+        ) -> R {
+            let mut guard = mutex.lock().expect("with_lock!: the Mutex is poisoned");
+            f(&mut guard)
+        }
+        with_lock(&$mutex, $f)
+    }};
+}
+
+/// Create a [`Box<str>`] from a string slice or a [`String`].
+///
+/// Unlike a [`String`], the result does not carry any spare capacity.
+///
+/// It is also able to create tuples if given more than one parameter.
+///
+/// # Example
+/// ```
+/// use sugars::box_str;
+/// # fn main() {
+/// let s = box_str!("Hello");
+/// assert_eq!("Hello", &*s);
+/// let owned = box_str!(String::from("World"));
+/// assert_eq!("World", &*owned);
+/// # }
+/// ```
+///
+/// [`Box<str>`]: ::std::boxed::Box
+#[macro_export]
+macro_rules! box_str {
+    ($e:expr) => {
+        <::std::boxed::Box<str> as ::std::convert::From<_>>::from($e)
+    };
+    // Trailing comma with single argument is ignored
+    ($e:expr,) => {
+        $crate::box_str!($e)
+    };
+    ($($e:expr),+ $(,)?) => {
+        ($($crate::box_str!($e)),+,)
+    };
+}
+
+/// Create a shared [`Arc<str>`] from a string slice or a [`String`].
+///
+/// Clones of the result share the same allocation, so it is a cheap way to pass around
+/// immutable strings.
+///
+/// It is also able to create tuples if given more than one parameter.
+///
+/// # Example
+/// ```
+/// use sugars::arc_str;
+/// # fn main() {
+/// let s = arc_str!("Hello");
+/// assert_eq!("Hello", &*s);
+/// let owned = arc_str!(String::from("World"));
+/// assert_eq!("World", &*owned);
+/// # }
+/// ```
+///
+/// [`Arc<str>`]: ::std::sync::Arc
+#[macro_export]
+macro_rules! arc_str {
+    ($e:expr) => {
+        <::std::sync::Arc<str> as ::std::convert::From<_>>::from($e)
+    };
+    // Trailing comma with single argument is ignored
+    ($e:expr,) => {
+        $crate::arc_str!($e)
+    };
+    ($($e:expr),+ $(,)?) => {
+        ($($crate::arc_str!($e)),+,)
+    };
+}
+
+/// Create a shared [`Rc<str>`] from a string slice or a [`String`].
+///
+/// Clones of the result share the same allocation, so it is a cheap way to pass around
+/// immutable strings.
+///
+/// It is also able to create tuples if given more than one parameter.
+///
+/// # Example
+/// ```
+/// use sugars::rc_str;
+/// # fn main() {
+/// let s = rc_str!("Hello");
+/// assert_eq!("Hello", &*s);
+/// let owned = rc_str!(String::from("World"));
+/// assert_eq!("World", &*owned);
+/// # }
+/// ```
+///
+/// [`Rc<str>`]: ::std::rc::Rc
+#[macro_export]
+macro_rules! rc_str {
+    ($e:expr) => {
+        <::std::rc::Rc<str> as ::std::convert::From<_>>::from($e)
+    };
+    // Trailing comma with single argument is ignored
+    ($e:expr,) => {
+        $crate::rc_str!($e)
+    };
+    ($($e:expr),+ $(,)?) => {
+        ($($crate::rc_str!($e)),+,)
+    };
+}
+
+/// Create a shared [`Arc<str>`] from a string slice or a [`String`].
+///
+/// An alias of [`arc_str`].
+///
+/// # Example
+/// ```
+/// use sugars::arcstr;
+/// # fn main() {
+/// let s = arcstr!("Hello");
+/// assert!(&*s == "Hello");
+/// # }
+/// ```
+///
+/// [`Arc<str>`]: ::std::sync::Arc
+/// [`arc_str`]: crate::arc_str
+#[macro_export]
+macro_rules! arcstr {
+    ($($e:expr),+ $(,)?) => {
+        $crate::arc_str!($($e),+)
+    };
+}
+
+/// Create a shared [`Rc<str>`] from a string slice or a [`String`].
+///
+/// An alias of [`rc_str`].
+///
+/// # Example
+/// ```
+/// use sugars::rcstr;
+/// # fn main() {
+/// let s = rcstr!(String::from("Hello"));
+/// assert!(&*s == "Hello");
+/// # }
+/// ```
+///
+/// [`Rc<str>`]: ::std::rc::Rc
+/// [`rc_str`]: crate::rc_str
+#[macro_export]
+macro_rules! rcstr {
+    ($($e:expr),+ $(,)?) => {
+        $crate::rc_str!($($e),+)
+    };
+}
+
+/// Create a new [`RefCell`] to share a mutable value.
+///
+/// A [`RefCell`] works for every type, but checks the borrows at runtime. For [`Copy`] types,
+/// prefer [`cell`], since a [`Cell`] has no borrow tracking overhead. To share the value between
+/// owners, use [`shared_mut_rc`].
+///
+/// It is also able to create tuples if given more than one parameter.
+///
+/// # Example
+/// ```
+/// use sugars::shared_mut;
+/// # fn main() {
+/// let names = shared_mut!(Vec::new());
+/// names.borrow_mut().push("ana");
+/// assert_eq!(vec!["ana"], *names.borrow());
+/// # }
+/// ```
+///
+/// [`RefCell`]: ::std::cell::RefCell
+/// [`Cell`]: ::std::cell::Cell
+/// [`cell`]: crate::cell
+/// [`shared_mut_rc`]: crate::shared_mut_rc
+#[macro_export]
+macro_rules! shared_mut {
+    ($e:expr) => {
+        $crate::refcell!($e)
+    };
+    ($e:expr,) => {
+        $crate::shared_mut!($e)
+    };
+    ($($e:expr),+ $(,)?) => {
+        ($($crate::shared_mut!($e)),+,)
+    };
+}
+
+/// Create a new [`Rc`]`<`[`RefCell`]`>` to share a mutable value between owners.
+///
+/// It is also able to create tuples if given more than one parameter.
+///
+/// # Example
+/// ```
+/// use std::rc::Rc;
+/// use sugars::shared_mut_rc;
+/// # fn main() {
+/// let counter = shared_mut_rc!(0);
+/// let other = Rc::clone(&counter);
+/// *other.borrow_mut() += 1;
+/// assert_eq!(1, *counter.borrow());
+/// # }
+/// ```
+///
+/// [`Rc`]: ::std::rc::Rc
+/// [`RefCell`]: ::std::cell::RefCell
+#[macro_export]
+macro_rules! shared_mut_rc {
+    ($e:expr) => {
+        ::std::rc::Rc::new(::std::cell::RefCell::new($e))
+    };
+    ($e:expr,) => {
+        $crate::shared_mut_rc!($e)
+    };
+    ($($e:expr),+ $(,)?) => {
+        ($($crate::shared_mut_rc!($e)),+,)
+    };
+}
+
+/// A value computed by a function on its first access, created by [`arc_lazy`].
+///
+/// It is backed by a [`OnceLock`], so it is thread safe and the function runs only once, even
+/// when accessed from several threads at the same time.
+///
+/// [`arc_lazy`]: crate::arc_lazy
+/// [`OnceLock`]: ::std::sync::OnceLock
+pub struct Lazy<T, F = fn() -> T> {
+    cell: ::std::sync::OnceLock<T>,
+    init: F,
+}
+
+impl<T, F: Fn() -> T> Lazy<T, F> {
+    /// Creates a new `Lazy` computing its value with `init`.
+    pub const fn new(init: F) -> Self {
+        Self {
+            cell: ::std::sync::OnceLock::new(),
+            init,
+        }
+    }
+
+    /// Returns the value, computing it if this is the first access.
+    pub fn get(&self) -> &T {
+        self.cell.get_or_init(&self.init)
+    }
+}
+
+impl<T, F: Fn() -> T> ::std::ops::Deref for Lazy<T, F> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.get()
+    }
+}
+
+/// Create a new [`Arc`] with a [`Lazy`] value, computed on the first access and shared by all
+/// the clones of the [`Arc`].
+///
+/// # Example
+/// ```
+/// use std::sync::Arc;
+/// use sugars::arc_lazy;
+/// # fn main() {
+/// let cache = arc_lazy!(|| (1..=10).product::<u64>());
+/// let other = Arc::clone(&cache);
+///
+/// assert_eq!(3_628_800, *other.get());
+/// assert_eq!(3_628_800, **cache);
+/// # }
+/// ```
+///
+/// [`Arc`]: ::std::sync::Arc
+/// [`Lazy`]: crate::Lazy
+#[macro_export]
+macro_rules! arc_lazy {
+    ($init:expr $(,)?) => {
+        ::std::sync::Arc::new($crate::Lazy::new($init))
+    };
+}
+
+/// A clone-on-write value shared through an [`Arc`], created by [`arc_cow`].
+///
+/// Unlike [`Cow`], it has no lifetime: every holder owns a handle to the same value, and cloning
+/// an `ArcCow` only bumps the reference count. Mutating through [`make_mut`] is done in place if
+/// this is the only handle, otherwise the value is cloned first, so the other holders never see
+/// the change.
+///
+/// [`Arc`]: ::std::sync::Arc
+/// [`Cow`]: ::std::borrow::Cow
+/// [`arc_cow`]: crate::arc_cow
+/// [`make_mut`]: ArcCow::make_mut
+#[derive(Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ArcCow<T> {
+    inner: ::std::sync::Arc<T>,
+}
+
+impl<T> ArcCow<T> {
+    /// Creates a new `ArcCow` holding `value`.
+    pub fn new(value: T) -> Self {
+        Self {
+            inner: ::std::sync::Arc::new(value),
+        }
+    }
+
+    /// Returns a mutable reference to the value, cloning it first if it is shared.
+    pub fn make_mut(&mut self) -> &mut T
+    where
+        T: Clone,
+    {
+        ::std::sync::Arc::make_mut(&mut self.inner)
+    }
+
+    /// Returns `true` if other handles point to the same value.
+    pub fn is_shared(&self) -> bool {
+        ::std::sync::Arc::strong_count(&self.inner) > 1
+            || ::std::sync::Arc::weak_count(&self.inner) > 0
+    }
+
+    /// Returns `true` if both handles point to the same value.
+    pub fn ptr_eq(this: &Self, other: &Self) -> bool {
+        ::std::sync::Arc::ptr_eq(&this.inner, &other.inner)
+    }
+
+    /// Returns the inner [`Arc`].
+    ///
+    /// [`Arc`]: ::std::sync::Arc
+    pub fn into_arc(self) -> ::std::sync::Arc<T> {
+        self.inner
+    }
+}
+
+impl<T> Clone for ArcCow<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: ::std::sync::Arc::clone(&self.inner),
+        }
+    }
+}
+
+impl<T> From<::std::sync::Arc<T>> for ArcCow<T> {
+    fn from(inner: ::std::sync::Arc<T>) -> Self {
+        Self { inner }
+    }
+}
+
+impl<T> ::std::ops::Deref for ArcCow<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.inner
+    }
+}
+
+/// Create a new [`ArcCow`], a clone-on-write value shared through an [`Arc`].
+///
+/// Clones of the [`ArcCow`] share the value, and [`make_mut`] only clones it when it is shared.
+/// An existing [`Arc`] can be wrapped with [`ArcCow::from`].
+///
+/// # Example
+/// ```
+/// use sugars::arc_cow;
+/// # fn main() {
+/// let mut config = arc_cow!(vec![1, 2, 3]);
+/// let snapshot = config.clone();
+///
+/// // Shared, so the value is cloned before being changed
+/// config.make_mut().push(4);
+///
+/// assert_eq!(vec![1, 2, 3, 4], *config);
+/// assert_eq!(vec![1, 2, 3], *snapshot);
+/// # }
+/// ```
+///
+/// [`Arc`]: ::std::sync::Arc
+/// [`ArcCow`]: crate::ArcCow
+/// [`ArcCow::from`]: crate::ArcCow
+/// [`make_mut`]: crate::ArcCow::make_mut
+#[macro_export]
+macro_rules! arc_cow {
+    ($e:expr) => {
+        $crate::ArcCow::new($e)
+    };
+    ($e:expr,) => {
+        $crate::arc_cow!($e)
+    };
+    ($($e:expr),+ $(,)?) => {
+        ($($crate::arc_cow!($e)),+,)
+    };
+}
+
+/// Downcast a [`Box`]`<dyn `[`Any`]`>` to a concrete type.
+///
+/// Returns the [`Box`] back as the error if the value is not of the given type.
+///
+/// # Example
+/// ```
+/// use std::any::Any;
+/// use sugars::downcast_box;
+/// # fn main() {
+/// let any: Box<dyn Any> = Box::new(10i32);
+/// assert_eq!(Box::new(10), downcast_box!(any, i32).unwrap());
+/// # }
+/// ```
+///
+/// [`Box`]: ::std::boxed::Box
+/// [`Any`]: ::std::any::Any
+#[macro_export]
+macro_rules! downcast_box {
+    ($e:expr, $t:ty $(,)?) => {
+        $e.downcast::<$t>()
+    };
+}
+
+/// Downcast a reference to `dyn `[`Any`] to a reference to a concrete type.
+///
+/// Returns [`None`] if the value is not of the given type.
+///
+/// # Example
+/// ```
+/// use std::any::Any;
+/// use sugars::downcast_ref;
+/// # fn main() {
+/// let any: &dyn Any = &"text";
+/// assert_eq!(Some(&"text"), downcast_ref!(any, &str));
+/// assert_eq!(None, downcast_ref!(any, String));
+/// # }
+/// ```
+///
+/// [`Any`]: ::std::any::Any
+#[macro_export]
+macro_rules! downcast_ref {
+    ($e:expr, $t:ty $(,)?) => {
+        $e.downcast_ref::<$t>()
+    };
+}
+
+/// Create a new [`Arc`]`<`[`Mutex`]`>` to share a mutable value between threads.
+///
+/// It is also able to create tuples if given more than one parameter.
+///
+/// # Example
+/// ```
+/// use std::{sync::Arc, thread};
+/// use sugars::arcmutex;
+/// # fn main() {
+/// let counter = arcmutex!(0);
+/// let other = Arc::clone(&counter);
+/// thread::spawn(move || *other.lock().unwrap() += 1).join().unwrap();
+/// assert_eq!(1, *counter.lock().unwrap());
+/// # }
+/// ```
+///
+/// [`Arc`]: ::std::sync::Arc
+/// [`Mutex`]: ::std::sync::Mutex
+#[macro_export]
+macro_rules! arcmutex {
+    ($e:expr) => {
+        ::std::sync::Arc::new(::std::sync::Mutex::new($e))
+    };
+    ($e:expr,) => {
+        $crate::arcmutex!($e)
+    };
+    ($($e:expr),+ $(,)?) => {
+        ($($crate::arcmutex!($e)),+,)
+    };
+}
+
+/// Create a new [`Rc`]`<`[`RefCell`]`>`.
+///
+/// It is also able to create tuples if given more than one parameter.
+///
+/// # Example
+/// ```
+/// use std::rc::Rc;
+/// use sugars::rccell;
+/// # fn main() {
+/// let node = rccell!(vec![1]);
+/// let other = Rc::clone(&node);
+/// other.borrow_mut().push(2);
+/// assert_eq!(vec![1, 2], *node.borrow());
+/// # }
+/// ```
+///
+/// [`Rc`]: ::std::rc::Rc
+/// [`RefCell`]: ::std::cell::RefCell
+#[macro_export]
+macro_rules! rccell {
+    ($e:expr) => {
+        ::std::rc::Rc::new(::std::cell::RefCell::new($e))
+    };
+    ($e:expr,) => {
+        $crate::rccell!($e)
+    };
+    ($($e:expr),+ $(,)?) => {
+        ($($crate::rccell!($e)),+,)
+    };
+}
+
+/// Create a [`Weak`] reference from an [`Rc`] or an [`Arc`], without consuming it.
+///
+/// Use `rc:` for [`Rc`] handles and `arc:` for [`Arc`] handles.
+///
+/// It is also able to create tuples if given more than one parameter.
+///
+/// # Example
+/// ```
+/// use std::{rc::Rc, sync::Arc};
+/// use sugars::weak;
+/// # fn main() {
+/// let parent = Rc::new("parent");
+/// let weak_parent = weak!(rc: parent);
+/// assert_eq!(Some(Rc::new("parent")), weak_parent.upgrade());
+///
+/// let shared = Arc::new(10);
+/// let weak_shared = weak!(arc: shared);
+/// drop(shared);
+/// assert_eq!(None, weak_shared.upgrade());
+/// # }
+/// ```
+///
+/// [`Weak`]: ::std::rc::Weak
+/// [`Rc`]: ::std::rc::Rc
+/// [`Arc`]: ::std::sync::Arc
+#[macro_export]
+macro_rules! weak {
+    (rc: $e:expr) => {
+        ::std::rc::Rc::downgrade(&$e)
+    };
+    (rc: $e:expr,) => {
+        $crate::weak!(rc: $e)
+    };
+    (rc: $($e:expr),+ $(,)?) => {
+        ($($crate::weak!(rc: $e)),+,)
+    };
+
+    (arc: $e:expr) => {
+        ::std::sync::Arc::downgrade(&$e)
+    };
+    (arc: $e:expr,) => {
+        $crate::weak!(arc: $e)
+    };
+    (arc: $($e:expr),+ $(,)?) => {
+        ($($crate::weak!(arc: $e)),+,)
+    };
+}
+
+/// Create a new pinned [`Box`] with [`Box::pin`].
+///
+/// It is also able to create tuples if given more than one parameter.
+///
+/// # Example
+/// ```
+/// use std::{future::Future, pin::Pin};
+/// use sugars::boxpin;
+/// # fn main() {
+/// let fut: Pin<Box<dyn Future<Output = i32>>> = boxpin!(async { 10 });
+/// # }
+/// ```
+///
+/// [`Box`]: ::std::boxed::Box
+/// [`Box::pin`]: ::std::boxed::Box::pin
+#[macro_export]
+macro_rules! boxpin {
+    ($e:expr) => {
+        ::std::boxed::Box::pin($e)
+    };
+    ($e:expr,) => {
+        $crate::boxpin!($e)
+    };
+    ($($e:expr),+ $(,)?) => {
+        ($($crate::boxpin!($e)),+,)
+    };
+}
+
+/// Box the value inside an [`Option`] as a trait object, turning an `Option<T>` into an
+/// `Option<Box<dyn Trait>>`.
+///
+/// # Example
+/// ```
+/// use std::fmt::Display;
+/// use sugars::box_opt_dyn;
+/// # fn main() {
+/// let shown: Option<Box<dyn Display>> = box_opt_dyn!(Display; Some(10));
+/// assert_eq!("10", shown.unwrap().to_string());
+/// # }
+/// ```
+#[macro_export]
+macro_rules! box_opt_dyn {
+    ($tr:path; $e:expr $(,)?) => {
+        ::std::option::Option::map($e, |v| {
+            ::std::boxed::Box::new(v) as ::std::boxed::Box<dyn $tr>
+        })
+    };
+}
+
+/// Create a boxed slice from a list of elements.
+///
+/// It also supports the `elem; n` repeat form, which requires the element to implement
+/// [`Clone`].
+///
+/// # Example
+/// ```
+/// use sugars::boxed_slice;
+/// # fn main() {
+/// let slice: Box<[i32]> = boxed_slice![1, 2, 3];
+/// assert_eq!(3, slice.len());
+///
+/// let zeros = boxed_slice![0u8; 4];
+/// assert_eq!(&[0, 0, 0, 0], &*zeros);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! boxed_slice {
+    () => {
+        ::std::vec::Vec::new().into_boxed_slice()
+    };
+    ($elem:expr; $n:expr) => {
+        ::std::vec![$elem; $n].into_boxed_slice()
+    };
+    ($($e:expr),+ $(,)?) => {
+        ::std::vec![$($e),+].into_boxed_slice()
+    };
+}
+
+/// Returns whether the value equals the default value of its type.
+///
+/// Used by the [`cell_take_or`] macro.
+///
+/// [`cell_take_or`]: crate::cell_take_or
+#[doc(hidden)]
+pub fn is_default<T: Default + PartialEq>(value: &T) -> bool {
+    *value == T::default()
+}
+
+/// Take the value out of a [`Cell`] or a [`RefCell`], returning the given fallback if the cell
+/// held the default value of its type.
+///
+/// The value is taken with [`Cell::take`] or [`RefCell::take`], so the cell is always left
+/// holding [`Default::default`]. The type must implement [`Default`] and [`PartialEq`], and the
+/// fallback is only evaluated when the taken value equals the default.
+///
+/// # Example
+/// ```
+/// use std::cell::Cell;
+/// use sugars::cell_take_or;
+/// # fn main() {
+/// let retries = Cell::new(3);
+/// assert_eq!(3, cell_take_or!(retries, 1));
+/// assert_eq!(0, retries.get());
+/// assert_eq!(1, cell_take_or!(retries, 1));
+/// # }
+/// ```
+///
+/// [`Cell`]: ::std::cell::Cell
+/// [`RefCell`]: ::std::cell::RefCell
+/// [`Cell::take`]: ::std::cell::Cell::take
+/// [`RefCell::take`]: ::std::cell::RefCell::take
+#[macro_export]
+macro_rules! cell_take_or {
+    ($cell:expr, $default:expr $(,)?) => {{
+        let taken = $cell.take();
+        if $crate::is_default(&taken) {
+            $default
+        } else {
+            taken
+        }
+    }};
+}
+
+/// Break a reference cycle by resetting a field of the value behind a pointer to its
+/// [`Default`].
+///
+/// The field must be interior mutable through a [`RefCell`], usually a
+/// `RefCell<Option<Rc<_>>>` pointing to the next node of the cycle. Its old value is dropped,
+/// releasing the strong reference it held, so the nodes of the cycle can then be freed.
+///
+/// # Panics
+/// Panics if the field is currently borrowed.
+///
+/// # Example
+/// ```
+/// use std::{cell::RefCell, rc::Rc};
+/// use sugars::break_cycle;
+/// # fn main() {
+/// #[derive(Default)]
+/// struct Node {
+///     next: RefCell<Option<Rc<Node>>>,
+/// }
+///
+/// let a = Rc::new(Node::default());
+/// let b = Rc::new(Node { next: RefCell::new(Some(Rc::clone(&a))) });
+/// *a.next.borrow_mut() = Some(Rc::clone(&b));
+/// assert_eq!(2, Rc::strong_count(&b));
+///
+/// break_cycle!(a, next);
+/// assert_eq!(1, Rc::strong_count(&b));
+/// # }
+/// ```
+///
+/// [`RefCell`]: ::std::cell::RefCell
+#[macro_export]
+macro_rules! break_cycle {
+    ($e:expr, $field:tt $(,)?) => {
+        ::std::mem::drop(::std::mem::take(&mut *$e.$field.borrow_mut()))
+    };
+}
+
+/// Declare lazily initialized globals, without the need of the `lazy_static` crate.
+///
+/// Each declaration generates a function with the given name that initializes the value on the
+/// first call and returns a `&'static` reference to it on every call. It is backed by
+/// [`OnceLock`], so it is thread safe and the initializer runs only once.
+///
+/// Unlike in a `static`, lifetimes can't be elided from references in the type, since it is
+/// also the return type of the generated function: write `&'static str` instead of `&str`.
+/// Elided lifetimes in `Fn` traits are fine and stay higher-ranked. The `;` after the last
+/// declaration is optional.
+///
+/// # Example
+/// ```
+/// use std::collections::HashMap;
+/// use sugars::{hmap, lazy_global};
+///
+/// lazy_global! {
+///     static CONFIG: HashMap<&'static str, i32> = hmap! {"a" => 1};
+///     pub static NAME: String = String::from("sugars");
+/// }
+///
+/// # fn main() {
+/// assert_eq!(CONFIG()["a"], 1);
+/// assert_eq!(NAME(), "sugars");
+/// # }
+/// ```
+///
+/// [`OnceLock`]: ::std::sync::OnceLock
+#[macro_export]
+macro_rules! lazy_global {
+    ($($(#[$attr:meta])* $vis:vis static $name:ident : $t:ty = $init:expr);+ $(;)?) => {
+        $(
+            $(#[$attr])*
+            #[allow(non_snake_case)]
+            $vis fn $name() -> &'static $t {
+                static CELL: ::std::sync::OnceLock<$t> = ::std::sync::OnceLock::new();
+                CELL.get_or_init(|| $init)
+            }
+        )+
+    };
+}
+
+#[cfg(test)]
+mod tests {
+
+    #[test]
+    fn boxed() {
+        assert_eq!(Box::new(10), boxed!(10));
+        assert_eq!(Box::new(Some("String")), boxed!(Some("String")));
+    }
+
+    #[test]
+    fn boxed_trailing_comma() {
+        assert_eq!(Box::new(10), boxed!(10,));
+        assert_eq!(Box::new(Some("String")), boxed!(Some("String"),));
+    }
+
+    #[test]
+    fn boxed_tuples() {
+        let expected1 = (Box::new(10), Box::new(11));
+        let expected2 = (Box::new(Some("String")), Box::new(Some("other_str")));
+        assert_eq!(expected1, boxed!(10, 11));
+        assert_eq!(expected2, boxed!(Some("String"), Some("other_str")));
+    }
+
+    #[test]
+    fn rc() {
+        use std::rc::Rc;
+        assert_eq!(Rc::new(10), rc!(10));
+        assert_eq!(Rc::new(Some("String")), rc!(Some("String")));
+    }
+
+    #[test]
+    fn rc_trailing_comma() {
+        use std::rc::Rc;
+        assert_eq!(Rc::new(10), rc!(10,));
+        assert_eq!(Rc::new(Some("String")), rc!(Some("String"),));
+    }
+
+    #[test]
+    fn rc_tuples() {
+        use std::rc::Rc;
+        let expected1 = (Rc::new(10), Rc::new(11));
+        let expected2 = (Rc::new(Some("String")), Rc::new(Some("other_str")));
+        assert_eq!(expected1, rc!(10, 11));
+        assert_eq!(expected2, rc!(Some("String"), Some("other_str")));
+    }
+
+    #[test]
+    fn cow() {
+        use std::borrow::Cow;
+
+        #[derive(Clone, PartialEq, Debug)]
+        struct Test(i32);
+
+        let test1 = String::from("Hello Cow");
+        let expected: Cow<str> = Cow::Borrowed(&test1);
+        let test: Cow<str> = cow!(&test1);
+
+        assert_eq!(expected, test);
+
+        let test2 = Test(10);
+        let expected: Cow<Test> = Cow::Borrowed(&test2);
+        let test = cow!(borrow test2);
+        assert_eq!(expected, test);
+
+        let expected: Cow<str> = Cow::Owned(String::from("Hello Cow"));
+        let test: Cow<str> = cow!(own String::from("Hello Cow"));
+
+        assert_eq!(expected, test);
+
+        let expected: Cow<Test> = Cow::Owned(Test(10));
+        let test = cow!(own Test(10));
+        assert_eq!(expected, test);
+    }
+
+    #[test]
+    fn cell() {
+        use std::cell::Cell;
+        assert_eq!(Cell::new(10), cell!(10));
+        assert_eq!(Cell::new(Some("String")), cell!(Some("String")));
+    }
+
+    #[test]
+    fn cell_trailing_comma() {
+        use std::cell::Cell;
+        assert_eq!(Cell::new(10), cell!(10,));
+        assert_eq!(Cell::new(Some("String")), cell!(Some("String"),));
+    }
+
+    #[test]
+    fn cell_tuples() {
+        use std::cell::Cell;
+        let expected1 = (Cell::new(10), Cell::new(11));
+        let expected2 = (Cell::new(Some("String")), Cell::new(Some("other_str")));
+        assert_eq!(expected1, cell!(10, 11));
+        assert_eq!(expected2, cell!(Some("String"), Some("other_str")));
+    }
+
+    #[test]
+    fn refcell() {
+        use std::cell::RefCell;
+        assert_eq!(RefCell::new(10), refcell!(10));
+        assert_eq!(RefCell::new(Some("String")), refcell!(Some("String")));
+    }
+
+    #[test]
+    fn refcell_trailing_comma() {
+        use std::cell::RefCell;
+        assert_eq!(RefCell::new(10), refcell!(10,));
+        assert_eq!(RefCell::new(Some("String")), refcell!(Some("String"),));
+    }
+
+    #[test]
+    fn refcell_tuples() {
+        use std::cell::RefCell;
+        let expected1 = (RefCell::new(10), RefCell::new(11));
+        let expected2 = (
+            RefCell::new(Some("String")),
+            RefCell::new(Some("other_str")),
+        );
+        assert_eq!(expected1, refcell!(10, 11));
+        assert_eq!(expected2, refcell!(Some("String"), Some("other_str")));
+    }
+
+    #[test]
+    fn arc() {
+        use std::sync::Arc;
+        assert_eq!(Arc::new(10), arc!(10));
+        assert_eq!(Arc::new(Some("String")), arc!(Some("String")));
+    }
+
+    #[test]
+    fn arc_trailing_comma() {
+        use std::sync::Arc;
+        assert_eq!(Arc::new(10), arc!(10,));
+        assert_eq!(Arc::new(Some("String")), arc!(Some("String"),));
+    }
+
+    #[test]
+    fn arc_tuples() {
+        use std::sync::Arc;
+        let expected1 = (Arc::new(10), Arc::new(11));
+        let expected2 = (Arc::new(Some("String")), Arc::new(Some("other_str")));
+        assert_eq!(expected1, arc!(10, 11));
+        assert_eq!(expected2, arc!(Some("String"), Some("other_str")));
+    }
+
+    #[test]
+    fn mutex() {
+        use std::sync::Mutex;
+
+        let mutex_expected = Mutex::new(Some("String"));
+        let mutex_test = mutex!(Some("String"));
+
+        let expected = mutex_expected.lock().unwrap();
+        let test = mutex_test.lock().unwrap();
+        assert_eq!(expected.is_some(), test.is_some());
+    }
+
+    #[test]
+    fn rwlock() {
+        use std::sync::RwLock;
+
+        let rwlk_expected = RwLock::new(Some("String"));
+        let rwlk_test = rwlock!(Some("String"));
+
+        let expected = rwlk_expected.read().unwrap();
+        let test = rwlk_test.read().unwrap();
+        assert_eq!(expected.is_some(), test.is_some());
+    }
+
+    #[test]
+    fn rwlock_trailing_comma() {
+        use std::sync::RwLock;
+
+        let test: RwLock<i32> = rwlock!(10,);
+        assert_eq!(10, *test.read().unwrap());
+    }
+
+    #[test]
+    fn rwlock_tuples() {
+        use std::sync::RwLock;
+
+        let (a, b): (RwLock<i32>, RwLock<&str>) = rwlock!(10, "String");
+        assert_eq!(10, *a.read().unwrap());
+        assert_eq!("String", *b.read().unwrap());
+
+        let (c, d): (RwLock<i32>, RwLock<i32>) = rwlock!(1, 2,);
+        assert_eq!(1, *c.read().unwrap());
+        assert_eq!(2, *d.read().unwrap());
+    }
+
+    #[test]
+    fn box_array() {
+        let test = box_array!(7u32; 100_000);
+
+        assert_eq!(100_000, test.len());
+        assert_eq!(7, test[0]);
+        assert_eq!(7, test[54_321]);
+        assert_eq!(7, test[99_999]);
+    }
+
+    #[test]
+    fn refcount() {
+        use std::rc::Rc;
+
+        let a = rc!(String::from("rc"));
+        assert_eq!((1, 0), refcount!(a));
+
+        let b = Rc::clone(&a);
+        let weak = Rc::downgrade(&a);
+        assert_eq!((2, 1), refcount!(a));
+        assert_eq!((2, 1), refcount!(b));
+
+        drop(b);
+        drop(weak);
+        assert_eq!((1, 0), refcount!(a));
+    }
+
+    #[test]
+    fn arc_refcount() {
+        use std::sync::Arc;
+
+        let a = arc!(String::from("arc"));
+        assert_eq!((1, 0), arc_refcount!(a));
+
+        let _b = Arc::clone(&a);
+        let _c = Arc::clone(&a);
+        let _weak = Arc::downgrade(&a);
+        assert_eq!((3, 1), arc_refcount!(a));
+    }
+
+    #[test]
+    fn into_inner() {
+        let c = cell!(10);
+        c.set(20);
+        assert_eq!(20, into_inner!(c));
+
+        let r = refcell!(String::from("Hello"));
+        r.borrow_mut().push_str(" World");
+        assert_eq!("Hello World", into_inner!(r));
+
+        let m = mutex!(vec![1]);
+        m.lock().unwrap().push(2);
+        assert_eq!(vec![1, 2], into_inner!(m));
+
+        let rw = rwlock!(Some("String"));
+        *rw.write().unwrap() = None;
+        assert_eq!(None, into_inner!(rw));
+    }
+
+    #[test]
+    fn into_inner_tuples() {
+        let expected = (1, "a", 'b', 2.0);
+        let test = into_inner!(cell!(1), refcell!("a"), mutex!('b'), rwlock!(2.0));
+
+        assert_eq!(expected, test);
+    }
+
+    #[test]
+    fn try_unwrap_rc() {
+        use std::rc::Rc;
+
+        assert_eq!(Ok(String::from("rc")), try_unwrap!(rc!(String::from("rc"))));
+
+        let a = rc!(10);
+        let b = Rc::clone(&a);
+        let test = try_unwrap!(a);
+        assert_eq!(Err(Rc::new(10)), test);
+        assert!(Rc::ptr_eq(&b, &test.unwrap_err()));
+    }
+
+    #[test]
+    fn try_unwrap_arc() {
+        use std::sync::Arc;
+
+        let a = arc!(vec![1, 2]);
+        let b = Arc::clone(&a);
+        let a = try_unwrap!(a).unwrap_err();
+
+        drop(b);
+        assert_eq!(Ok(vec![1, 2]), try_unwrap!(a));
+    }
+
+    #[test]
+    fn replace_with() {
+        let number = refcell!(5);
+        replace_with!(number, |old| *old * 2);
+        assert_eq!(10, *number.borrow());
+
+        let string = refcell!(String::from("Hello"));
+        replace_with!(string, |old| format!("{} World", old));
+        replace_with!(string, |old| old.to_uppercase());
+        assert_eq!("HELLO WORLD", *string.borrow());
+    }
+
+    #[test]
+    fn box_option() {
+        assert_eq!(
+            Some(Box::new(String::from("boxed"))),
+            box_option!(Some(String::from("boxed")))
+        );
+
+        let none: Option<Vec<u8>> = None;
+        assert_eq!(None, box_option!(none));
+    }
+
+    #[test]
+    fn box_result() {
+        let ok: Result<_, String> = Ok([1, 2, 3]);
+        assert_eq!(Ok(Box::new([1, 2, 3])), box_result!(ok));
+
+        let err: Result<i32, _> = "a".parse::<i32>();
+        assert_eq!(err.clone().unwrap_err(), box_result!(err).unwrap_err());
+    }
+
+    #[test]
+    fn cell_update() {
+        let counter = cell!(0);
+        for _ in 0..5 {
+            cell_update!(counter, |v| v + 1);
+        }
+        assert_eq!(5, counter.get());
+
+        let point = cell!((1, 2));
+        let test = cell_update!(point, |(x, y)| (y * 10, x - 1));
+        assert_eq!((20, 0), test);
+        assert_eq!((20, 0), point.get());
+    }
+
+    #[test]
+    fn with_lock() {
+        let m = mutex!(vec![0]);
+
+        for i in 1..4 {
+            let len = with_lock!(m, |v| {
+                v.push(i);
+                v.len()
+            });
+            assert_eq!(i + 1, len);
+        }
+
+        // The lock was released, so it can be taken again in the same thread
+        let guard = m.try_lock().unwrap();
+        assert_eq!(vec![0, 1, 2, 3], *guard);
+    }
+
+    #[test]
+    fn with_lock_shared() {
+        use std::{sync::Arc, thread};
+
+        let counter = arc!(mutex!(0));
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let counter = Arc::clone(&counter);
+                thread::spawn(move || with_lock!(counter, |c| *c += 1))
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        assert_eq!(4, with_lock!(counter, |c| *c));
+    }
+
+    #[test]
+    fn box_str() {
+        let test = box_str!("literal");
+        assert_eq!("literal", &*test);
+
+        let mut owned = String::with_capacity(64);
+        owned.push_str("owned");
+        let test: Box<str> = box_str!(owned);
+        assert_eq!("owned", &*test);
+        assert_eq!(5, test.len());
+
+        let (a, b) = box_str!("a", String::from("b"));
+        assert_eq!(("a", "b"), (&*a, &*b));
+    }
+
+    #[test]
+    fn arc_str() {
+        use std::sync::Arc;
+
+        let test = arc_str!(String::from("shared"));
+        let clone = Arc::clone(&test);
+        assert_eq!("shared", &*clone);
+        assert!(Arc::ptr_eq(&test, &clone));
+        assert_eq!(2, Arc::strong_count(&test));
+    }
+
+    #[test]
+    fn rc_str() {
+        use std::rc::Rc;
+
+        let test = rc_str!("shared");
+        let clone = Rc::clone(&test);
+        assert_eq!("shared", &*clone);
+        assert!(Rc::ptr_eq(&test, &clone));
+    }
+
+    #[test]
+    fn shared_mut() {
+        let test = shared_mut!(String::from("Hello"));
+        test.borrow_mut().push_str(" World");
+        assert_eq!("Hello World", *test.borrow());
+
+        let (a, b) = shared_mut!(1, vec![2]);
+        *a.borrow_mut() += 1;
+        b.borrow_mut().push(3);
+        assert_eq!(2, *a.borrow());
+        assert_eq!(vec![2, 3], *b.borrow());
+    }
+
+    #[test]
+    fn shared_mut_rc() {
+        use std::rc::Rc;
+
+        let test = shared_mut_rc!(vec![1]);
+        let other = Rc::clone(&test);
+        other.borrow_mut().push(2);
+        assert_eq!(vec![1, 2], *test.borrow());
+        assert_eq!(2, Rc::strong_count(&test));
+    }
+
+    #[test]
+    fn arc_lazy() {
+        use std::sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        };
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&calls);
+        let lazy = arc_lazy!(move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+            String::from("computed")
+        });
+        assert_eq!(0, calls.load(Ordering::SeqCst));
+
+        let other = Arc::clone(&lazy);
+        let handle = std::thread::spawn(move || other.get().len());
+        assert_eq!("computed", lazy.get());
+        assert_eq!(8, handle.join().unwrap());
+        assert_eq!("computed", &**lazy);
+        assert_eq!(1, calls.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn downcast_box() {
+        use std::any::Any;
+
+        let any: Box<dyn Any> = Box::new(String::from("boxed"));
+        let any = downcast_box!(any, i32).unwrap_err();
+        assert_eq!("boxed", *downcast_box!(any, String).unwrap());
+
+        let any: Box<dyn Any + Send> = Box::new(vec![1, 2]);
+        assert_eq!(vec![1, 2], *downcast_box!(any, Vec<i32>).unwrap());
+    }
+
+    #[test]
+    fn downcast_ref() {
+        use std::any::Any;
+
+        let value = 10u8;
+        let any: &dyn Any = &value;
+        assert_eq!(Some(&10), downcast_ref!(any, u8));
+        assert_eq!(None, downcast_ref!(any, u16));
+
+        let boxed: Box<dyn Any> = Box::new(String::from("boxed"));
+        assert_eq!(
+            Some("boxed"),
+            downcast_ref!(boxed, String).map(String::as_str)
+        );
+    }
+
+    #[test]
+    fn arcmutex() {
+        use std::sync::Arc;
+
+        let test = arcmutex!(vec![1]);
+        let other = Arc::clone(&test);
+        other.lock().unwrap().push(2);
+        assert_eq!(vec![1, 2], *test.lock().unwrap());
+        assert_eq!(2, Arc::strong_count(&test));
+    }
+
+    #[test]
+    fn arcmutex_tuples() {
+        use std::sync::{Arc, Mutex};
+
+        let (a, b): (Arc<Mutex<i32>>, Arc<Mutex<&str>>) = arcmutex!(10, "String",);
+        assert_eq!(10, *a.lock().unwrap());
+        assert_eq!("String", *b.lock().unwrap());
+    }
+
+    #[test]
+    fn rccell() {
+        use std::rc::Rc;
+
+        let test = rccell!(1);
+        let a = Rc::clone(&test);
+        let b = Rc::clone(&test);
+        *a.borrow_mut() += 10;
+        *b.borrow_mut() *= 2;
+        assert_eq!(22, *test.borrow());
+        assert_eq!(3, Rc::strong_count(&test));
+    }
+
+    #[test]
+    fn rccell_tuples() {
+        use std::{cell::RefCell, rc::Rc};
+
+        let (a, b): (Rc<RefCell<i32>>, Rc<RefCell<&str>>) = rccell!(10, "String");
+        *a.borrow_mut() += 1;
+        assert_eq!(11, *a.borrow());
+        assert_eq!("String", *b.borrow());
+    }
+
+    #[test]
+    fn weak_rc() {
+        use std::rc::Rc;
+
+        let strong = Rc::new(String::from("node"));
+        let test = weak!(rc: strong);
+        assert_eq!(Some("node"), test.upgrade().as_deref().map(String::as_str));
+        assert_eq!(1, Rc::weak_count(&strong));
+
+        drop(strong);
+        assert!(test.upgrade().is_none());
+    }
+
+    #[test]
+    fn weak_arc() {
+        use std::sync::Arc;
+
+        let strong = Arc::new(10);
+        let test = weak!(arc: strong,);
+        assert_eq!(Some(10), test.upgrade().map(|x| *x));
+
+        drop(strong);
+        assert!(test.upgrade().is_none());
+    }
+
+    #[test]
+    fn weak_tuples() {
+        use std::{rc::Rc, sync::Arc};
+
+        let (a, b) = (Rc::new(1), Rc::new(2));
+        let (weak_a, weak_b) = weak!(rc: a, b);
+        drop(a);
+        assert!(weak_a.upgrade().is_none());
+        assert_eq!(Some(2), weak_b.upgrade().map(|x| *x));
+
+        let (c, d) = (Arc::new('c'), Arc::new('d'));
+        let (weak_c, weak_d) = weak!(arc: c, d,);
+        drop(d);
+        assert_eq!(Some('c'), weak_c.upgrade().map(|x| *x));
+        assert!(weak_d.upgrade().is_none());
+    }
+
+    #[test]
+    fn boxpin() {
+        use std::pin::Pin;
+
+        let mut test: Pin<Box<i32>> = boxpin!(5);
+        test.set(6);
+        assert_eq!(6, *test);
+    }
+
+    #[test]
+    fn boxpin_tuples() {
+        use std::pin::Pin;
+
+        let (a, b): (Pin<Box<i32>>, Pin<Box<&str>>) = boxpin!(10, "String",);
+        assert_eq!(10, *a);
+        assert_eq!("String", *b);
+    }
+
+    #[test]
+    fn box_opt_dyn() {
+        trait Plugin {
+            fn name(&self) -> String;
+        }
+
+        struct Logger;
+
+        impl Plugin for Logger {
+            fn name(&self) -> String {
+                String::from("logger")
+            }
+        }
+
+        let test = box_opt_dyn!(Plugin; Some(Logger));
+        assert_eq!("logger", test.unwrap().name());
+
+        let none: Option<Logger> = None;
+        assert!(box_opt_dyn!(Plugin; none).is_none());
+
+        let f = box_opt_dyn!(Fn(i32) -> i32; Some(|x| x * 2));
+        assert_eq!(Some(8), f.map(|f| f(4)));
+    }
+
+    #[test]
+    fn boxed_slice() {
+        let test = boxed_slice![1, 2, 3];
+        assert_eq!(3, test.len());
+        assert_eq!(vec![1, 2, 3].into_boxed_slice(), test);
+
+        let test = boxed_slice![String::from("a"); 2];
+        assert_eq!(vec![String::from("a"); 2].into_boxed_slice(), test);
+
+        let test: Box<[u8]> = boxed_slice![];
+        assert!(test.is_empty());
+    }
+
+    #[test]
+    fn arcstr() {
+        use std::sync::Arc;
+
+        let test = arcstr!("shared");
+        let clone = Arc::clone(&test);
+        assert!(&*clone == "shared");
+
+        let test = arcstr!(String::from("owned"));
+        assert!(&*test.clone() == "owned");
+    }
+
+    #[test]
+    fn rcstr() {
+        use std::rc::Rc;
+
+        let test = rcstr!(String::from("shared"));
+        let clone = Rc::clone(&test);
+        assert!(&*clone == "shared");
+        assert!(Rc::ptr_eq(&test, &clone));
+
+        let (a, b) = rcstr!("a", "b");
+        assert!(&*a == "a" && &*b == "b");
+    }
+
+    #[test]
+    fn cow_slices() {
+        use std::borrow::Cow;
+
+        let v = vec![1, 2, 3];
+        let arr = [1, 2, 3];
+
+        let expected: Cow<[i32]> = Cow::Borrowed(&v);
+        assert_eq!(expected, cow!(&v[..]));
+        assert!(matches!(cow!(&v[..]), Cow::Borrowed(_)));
+        assert!(matches!(cow!(&arr[..]), Cow::Borrowed(_)));
+
+        let test = cow!(borrow slice v);
+        assert!(matches!(test, Cow::Borrowed(s) if s == [1, 2, 3]));
+        let test = cow!(borrow slice arr);
+        assert!(matches!(test, Cow::Borrowed(s) if s == [1, 2, 3]));
+        let test: Cow<[i32]> = cow!(borrow arr);
+        assert!(matches!(test, Cow::Borrowed(_)));
+
+        let expected: Cow<[i32]> = Cow::Owned(vec![1, 2, 3]);
+        let test = cow!(own slice v.clone());
+        assert_eq!(expected, test);
+        assert!(matches!(test, Cow::Owned(_)));
+        assert!(matches!(cow!(own slice arr), Cow::Owned(o) if o == v));
+    }
+
+    #[test]
+    fn cell_take_or() {
+        use std::cell::{Cell, RefCell};
+
+        let test = Cell::new(Some(10));
+        assert_eq!(Some(10), cell_take_or!(test, Some(0)));
+        assert_eq!(None, test.get());
+        assert_eq!(Some(0), cell_take_or!(test, Some(0)));
+
+        let test = RefCell::new(String::from("state"));
+        assert_eq!("state", cell_take_or!(test, String::from("empty")));
+        assert!(test.borrow().is_empty());
+        assert_eq!("empty", cell_take_or!(test, String::from("empty")));
+    }
+
+    #[test]
+    fn arc_cow_unique_in_place() {
+        let mut v = arc_cow!(vec![1, 2, 3]);
+        let before = v.as_ptr();
+        assert!(!v.is_shared());
+
+        v.make_mut().push(4);
+
+        assert_eq!(vec![1, 2, 3, 4], *v);
+        assert_eq!(before, v.as_ptr());
+    }
+
+    #[test]
+    fn arc_cow_shared_clones() {
+        use crate::ArcCow;
+
+        let mut v = arc_cow!(String::from("shared"));
+        let other = v.clone();
+        assert!(v.is_shared());
+        assert!(ArcCow::ptr_eq(&v, &other));
+
+        v.make_mut().push_str(" and changed");
+
+        assert_eq!("shared and changed", *v);
+        assert_eq!("shared", *other);
+        assert!(!ArcCow::ptr_eq(&v, &other));
+        assert!(!v.is_shared());
+    }
+
+    #[test]
+    fn arc_cow_from_arc() {
+        use crate::ArcCow;
+        use std::sync::Arc;
+
+        let arc = Arc::new(10);
+        let mut v = ArcCow::from(Arc::clone(&arc));
+
+        *v.make_mut() += 1;
+
+        assert_eq!(11, *v);
+        assert_eq!(10, *arc);
+    }
+
+    #[test]
+    fn arc_cow_tuples() {
+        let (a, b) = arc_cow!(1, "two");
+
+        assert_eq!(1, *a);
+        assert_eq!("two", *b);
+    }
+
+    #[test]
+    fn break_cycle_two_nodes() {
+        use std::{cell::RefCell, rc::Rc};
+
+        struct Node {
+            name: &'static str,
+            next: RefCell<Option<Rc<Node>>>,
+        }
+
+        let a = Rc::new(Node {
+            name: "a",
+            next: RefCell::new(None),
+        });
+        let b = Rc::new(Node {
+            name: "b",
+            next: RefCell::new(Some(Rc::clone(&a))),
+        });
+        *a.next.borrow_mut() = Some(Rc::clone(&b));
+        let (weak_a, weak_b) = (Rc::downgrade(&a), Rc::downgrade(&b));
+        assert_eq!("b", a.next.borrow().as_ref().unwrap().name);
+        assert_eq!(2, Rc::strong_count(&a));
+        assert_eq!(2, Rc::strong_count(&b));
+
+        break_cycle!(a, next);
+        assert!(a.next.borrow().is_none());
+        assert_eq!(1, Rc::strong_count(&b));
+
+        drop(a);
+        drop(b);
+        assert_eq!(0, weak_a.strong_count());
+        assert_eq!(0, weak_b.strong_count());
+    }
+
+    #[test]
+    fn break_cycle_tuple_field() {
+        use std::{cell::RefCell, rc::Rc};
+
+        struct Node(RefCell<Vec<Rc<Node>>>);
+
+        let node = Rc::new(Node(RefCell::new(Vec::new())));
+        node.0.borrow_mut().push(Rc::clone(&node));
+        let weak = Rc::downgrade(&node);
+        assert_eq!(2, Rc::strong_count(&node));
+
+        break_cycle!(node, 0);
+        drop(node);
+        assert_eq!(0, weak.strong_count());
+    }
+
+    #[test]
+    fn lazy_global() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        static CALLS: AtomicUsize = AtomicUsize::new(0);
+
+        lazy_global! {
+            static VALUES: Vec<i32> = {
+                CALLS.fetch_add(1, Ordering::SeqCst);
+                vec![1, 2, 3]
+            };
+        }
+
+        assert_eq!(&[1, 2, 3], VALUES().as_slice());
+        assert_eq!(&[1, 2, 3], VALUES().as_slice());
+        assert_eq!(1, CALLS.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn lazy_global_static_lifetimes() {
+        use crate::hmap;
+        use std::collections::HashMap;
+
+        lazy_global!(static CONFIG: HashMap<&'static str, i32> = hmap!{"a" => 1});
+        lazy_global! {
+            static A: HashMap<&'static str, Vec<(&'static str, Option<&'static str>)>> = HashMap::new();
+            static B: HashMap<&'static str, Vec<(&'static str, Option<&'static str>)>> = HashMap::new();
+            static C: HashMap<&'static str, Vec<(&'static str, Option<&'static str>)>> = HashMap::new();
+            static D: HashMap<&'static str, Vec<(&'static str, Option<&'static str>)>> = HashMap::new();
+            static E: HashMap<&'static str, Vec<(&'static str, Option<&'static str>)>> = HashMap::new();
+            static F: HashMap<&'static str, Vec<(&'static str, Option<&'static str>)>> = HashMap::new();
+            static G: HashMap<&'static str, Vec<(&'static str, Option<&'static str>)>> = HashMap::new();
+            static H: HashMap<&'static str, Vec<(&'static str, Option<&'static str>)>> = HashMap::new();
+        }
+
+        assert_eq!(1, CONFIG()["a"]);
+        assert_eq!(1, CONFIG().len());
+        for global in [A, B, C, D, E, F, G, H] {
+            assert!(global().is_empty());
+        }
+    }
+
+    #[test]
+    fn lazy_global_fn_lifetimes() {
+        lazy_global! {
+            static LEN: Box<dyn Fn(&str) -> usize + Send + Sync> = Box::new(|s| s.len());
+        }
+
+        let local = String::from("local");
+        assert_eq!(5, LEN()(&local));
+        assert_eq!(5, LEN()(local.as_str()));
     }
 }
@@ -3,12 +3,18 @@
 /// Creates a [`Duration`] object following a time pattern.
 ///
 /// **Paterns:**
+/// * week: weeks
+/// * day: days
+/// * hour: hours
 /// * min: minutes
 /// * sec: seconds
 /// * nano: nanoseconds
 /// * micro: microseconds
 /// * milli: milliseconds
 ///
+/// The quantity can be any expression evaluating to an integer, and several patterns separated
+/// by commas are added together into a single [`Duration`].
+///
 /// # Examples
 /// ```rust
 /// use sugars::dur;
@@ -17,53 +23,147 @@
 /// let d = dur!(10 sec);
 /// // Sleeps for 10 seconds
 /// std::thread::sleep(d);
+///
+/// assert_eq!(std::time::Duration::from_secs(90), dur!(1 min, 30 sec));
+///
+/// let retries = vec![1, 2, 3];
+/// assert_eq!(std::time::Duration::from_secs(6), dur!(retries.len() as u64 * 2 sec));
 /// # }
 /// ```
 ///
 /// [`Duration`]: ::std::time::Duration
 #[macro_export]
 macro_rules! dur {
-    ($e:literal min) => {{
-        let min2sec = $e * 60;
-        ::std::time::Duration::from_secs(min2sec)
+    (@unit week $e:expr) => {{
+        let week2sec = $e * 604_800;
+        ::std::time::Duration::from_secs(week2sec)
+    }};
+    (@unit day $e:expr) => {{
+        let day2sec = $e * 86_400;
+        ::std::time::Duration::from_secs(day2sec)
     }};
-    ($i:ident min) => {{
-        let min2sec = $i * 60;
+    (@unit hour $e:expr) => {{
+        let hour2sec = $e * 3_600;
+        ::std::time::Duration::from_secs(hour2sec)
+    }};
+    (@unit min $e:expr) => {{
+        let min2sec = $e * 60;
         ::std::time::Duration::from_secs(min2sec)
     }};
-
-    ($e:literal sec) => {
+    (@unit sec $e:expr) => {
         ::std::time::Duration::from_secs($e)
     };
-    ($i:ident sec) => {
-        ::std::time::Duration::from_secs($i)
+    (@unit nano $e:expr) => {
+        ::std::time::Duration::from_nanos($e)
+    };
+    (@unit micro $e:expr) => {
+        ::std::time::Duration::from_micros($e)
+    };
+    (@unit milli $e:expr) => {
+        ::std::time::Duration::from_millis($e)
     };
 
-    ($e:literal nano) => {
-        ::std::time::Duration::from_nanos($e)
+    // An `expr` fragment cannot be followed by the unit, so the tokens of each quantity are
+    // collected one at a time until reaching an identifier that ends the input or is followed
+    // by a comma, which is the unit.
+    (@munch [$($acc:expr),*] [$($e:tt)+] $unit:ident $(,)?) => {{
+        let total = ::std::time::Duration::ZERO;
+        $(
+            let total = total
+                .checked_add($acc)
+                .expect("overflow when adding durations");
+        )*
+        total
+            .checked_add($crate::dur!(@unit $unit ($($e)+)))
+            .expect("overflow when adding durations")
+    }};
+    (@munch [$($acc:expr),*] [$($e:tt)+] $unit:ident, $($rest:tt)+) => {
+        $crate::dur!(@munch [$($acc,)* $crate::dur!(@unit $unit ($($e)+))] [] $($rest)+)
     };
-    ($i:ident nano) => {
-        ::std::time::Duration::from_nanos($i)
+    (@munch $acc:tt [$($e:tt)*] $next:tt $($rest:tt)+) => {
+        $crate::dur!(@munch $acc [$($e)* $next] $($rest)+)
     };
 
-    ($e:literal micro) => {
-        ::std::time::Duration::from_micros($e)
+    ($($tokens:tt)+) => {
+        $crate::dur!(@munch [] [] $($tokens)+)
+    };
+}
+
+/// Creates a [`Duration`] object from a floating point quantity following a time pattern.
+///
+/// It accepts the same patterns as [`dur`], but the quantity is a [`f64`], which is handy for
+/// fractional values like `1.5 sec`.
+///
+/// # Panics
+/// Panics if the quantity is negative, overflows [`Duration`] or is not finite.
+///
+/// # Examples
+/// ```rust
+/// use std::time::Duration;
+/// use sugars::durf;
+///
+/// # fn main() {
+/// assert_eq!(Duration::from_millis(1_500), durf!(1.5 sec));
+/// assert_eq!(Duration::from_micros(2_500), durf!(2.5 milli));
+///
+/// let factor = 0.25;
+/// assert_eq!(Duration::from_secs(15), durf!(factor * 1.0 min));
+/// # }
+/// ```
+///
+/// [`Duration`]: ::std::time::Duration
+/// [`dur`]: crate::dur
+#[macro_export]
+macro_rules! durf {
+    (@unit week $e:expr) => {
+        ::std::time::Duration::from_secs_f64($e * 604_800.0)
+    };
+    (@unit day $e:expr) => {
+        ::std::time::Duration::from_secs_f64($e * 86_400.0)
+    };
+    (@unit hour $e:expr) => {
+        ::std::time::Duration::from_secs_f64($e * 3_600.0)
+    };
+    (@unit min $e:expr) => {
+        ::std::time::Duration::from_secs_f64($e * 60.0)
     };
-    ($i:ident micro) => {
-        ::std::time::Duration::from_micros($i)
+    (@unit sec $e:expr) => {
+        ::std::time::Duration::from_secs_f64($e)
+    };
+    (@unit milli $e:expr) => {
+        ::std::time::Duration::from_secs_f64($e / 1_000.0)
+    };
+    (@unit micro $e:expr) => {
+        ::std::time::Duration::from_secs_f64($e / 1_000_000.0)
+    };
+    (@unit nano $e:expr) => {
+        ::std::time::Duration::from_secs_f64($e / 1_000_000_000.0)
     };
 
-    ($e:literal milli) => {
-        ::std::time::Duration::from_millis($e)
+    // Like in `dur!`, the tokens of the quantity are collected until only the unit is left.
+    (@munch [$($e:tt)+] $unit:ident) => {
+        $crate::durf!(@unit $unit ($($e)+))
     };
-    ($i:ident milli) => {
-        ::std::time::Duration::from_millis($i)
+    (@munch [$($e:tt)*] $next:tt $($rest:tt)+) => {
+        $crate::durf!(@munch [$($e)* $next] $($rest)+)
+    };
+
+    ($e:literal $unit:ident) => {
+        $crate::durf!(@unit $unit $e)
+    };
+    ($($tokens:tt)+) => {
+        $crate::durf!(@munch [] $($tokens)+)
     };
 }
 
 /// Makes a thread sleep a amount following a time pattern.
 ///
+/// It accepts everything [`dur`] does, including combined patterns and computed quantities.
+///
 /// **Paterns:**
+/// * week: weeks
+/// * day: days
+/// * hour: hours
 /// * min: minutes
 /// * sec: seconds
 /// * nano: nanoseconds
@@ -76,56 +176,48 @@ macro_rules! dur {
 /// # fn main() {
 /// // Thread sleeps for 10 seconds
 /// sleep!(10 sec);
+///
+/// // Thread sleeps for 1.5 seconds
+/// let half = 500;
+/// sleep!(1 sec, half milli);
 /// # }
 /// ```
+///
+/// [`dur`]: crate::dur
 #[macro_export]
 macro_rules! sleep {
-    ($e:literal min) => {{
-        let min2sec = $e * 60;
-        let dur = ::std::time::Duration::from_secs(min2sec);
-        ::std::thread::sleep(dur);
-    }};
-    ($i:ident min) => {{
-        let min2sec = $i * 60;
-        let dur = ::std::time::Duration::from_secs(min2sec);
-        ::std::thread::sleep(dur);
-    }};
-
-    ($e:literal sec) => {{
-        let dur = ::std::time::Duration::from_secs($e);
-        ::std::thread::sleep(dur);
-    }};
-    ($i:ident sec) => {{
-        let dur = ::std::time::Duration::from_secs($i);
-        ::std::thread::sleep(dur);
-    }};
-
-    ($e:literal nano) => {{
-        let dur = ::std::time::Duration::from_nanos($e);
-        ::std::thread::sleep(dur);
-    }};
-    ($i:ident nano) => {{
-        let dur = ::std::time::Duration::from_nanos($i);
-        ::std::thread::sleep(dur);
-    }};
-
-    ($e:literal micro) => {{
-        let dur = ::std::time::Duration::from_micros($e);
-        ::std::thread::sleep(dur);
-    }};
-    ($i:ident micro) => {{
-        let dur = ::std::time::Duration::from_micros($i);
-        ::std::thread::sleep(dur);
-    }};
+    ($($tokens:tt)+) => {
+        ::std::thread::sleep($crate::dur!($($tokens)+))
+    };
+}
 
-    ($e:literal milli) => {{
-        let dur = ::std::time::Duration::from_millis($e);
-        ::std::thread::sleep(dur);
-    }};
-    ($i:ident milli) => {{
-        let dur = ::std::time::Duration::from_millis($i);
-        ::std::thread::sleep(dur);
-    }};
+/// Makes the current task sleep an amount following a time pattern, without blocking the
+/// executor thread.
+///
+/// It accepts the same time patterns as [`sleep`], but expands to an awaited
+/// `tokio::time::sleep`, so it can only be used inside `async` code running on a [tokio]
+/// runtime with the time driver enabled.
+///
+/// Requires the `tokio` feature.
+///
+/// # Examples
+/// ```rust
+/// # #[cfg(feature = "tokio")]
+/// async fn poll() {
+///     use sugars::sleep_async;
+///
+///     sleep_async!(200 milli);
+/// }
+/// ```
+///
+/// [`sleep`]: crate::sleep
+/// [tokio]: https://crates.io/crates/tokio
+#[cfg(feature = "tokio")]
+#[macro_export]
+macro_rules! sleep_async {
+    ($($tokens:tt)+) => {
+        $crate::tokio::time::sleep($crate::dur!($($tokens)+)).await
+    };
 }
 
 /// Print out the time it took to execute a given expression in seconds.
@@ -169,8 +261,38 @@ macro_rules! sleep {
 /// let (a, b) = time!(some_comp(), another_comp());
 /// # }
 /// ```
+///
+/// **With a custom output:**
+///
+/// The line can be written to any [`Write`] implementor instead of stderr. Errors while writing
+/// are ignored.
+///
+/// ```rust
+/// use sugars::time;
+/// # fn main() {
+/// let mut log = Vec::new();
+/// let x = time!(to: log, 100 + 20);
+///
+/// assert_eq!(120, x);
+/// assert!(String::from_utf8(log).unwrap().starts_with("100 + 20 "));
+/// # }
+/// ```
+///
+/// [`Write`]: ::std::io::Write
 #[macro_export]
 macro_rules! time {
+    (to: $w:expr, $e:expr $(,)?) => {{
+        let time = ::std::time::Instant::now();
+        match $e {
+            tmp => {
+                let _ = ::std::io::Write::write_fmt(
+                    &mut $w,
+                    format_args!("{} {:.6} seconds\n", stringify!($e), time.elapsed().as_secs_f64()),
+                );
+                tmp
+            }
+        }
+    }};
     ($e:expr) => {{
         let time = ::std::time::Instant::now();
         match $e {
@@ -187,60 +309,1218 @@ macro_rules! time {
     };
 }
 
-#[cfg(test)]
-mod tests {
-    use std::time::Duration;
-
-    #[test]
-    fn dur_literal_min() {
-        let expected = Duration::from_secs(10 * 60);
-        let test = dur!(10 min);
-
-        assert_eq!(expected, test);
-    }
+/// Evaluates an expression and returns it together with the [`Duration`] it took, without
+/// printing anything.
+///
+/// Like [`time`], this also supports more than one expression, returning a tuple with a
+/// `(value, Duration)` pair for each one.
+///
+/// # Example
+/// ```rust
+/// use sugars::{sleep, time_with};
+/// # fn main() {
+/// let (value, elapsed) = time_with!({
+///     sleep!(10 milli);
+///     42
+/// });
+///
+/// assert_eq!(42, value);
+/// assert!(elapsed >= std::time::Duration::from_millis(10));
+/// # }
+/// ```
+///
+/// [`Duration`]: ::std::time::Duration
+/// [`time`]: crate::time
+#[macro_export]
+macro_rules! time_with {
+    ($e:expr) => {{
+        let time = ::std::time::Instant::now();
+        match $e {
+            tmp => (tmp, time.elapsed()),
+        }
+    }};
+    // Trailing comma with single argument is ignored
+    ($e:expr,) => { $crate::time_with!($e) };
+    ($($e:expr),+ $(,)?) => {
+        ($($crate::time_with!($e)),+,)
+    };
+}
 
-    #[test]
-    fn dur_identifier_min() {
-        let expected = Duration::from_secs(10 * 60);
-        let x = 10;
-        let test = dur!(x min);
+/// Calls the closure with the instant `timeout` from now, giving the closure passed to
+/// [`with_timeout_poll`] its expected signature.
+///
+/// [`with_timeout_poll`]: crate::with_timeout_poll
+#[doc(hidden)]
+pub fn poll_deadline<T, F>(timeout: ::std::time::Duration, f: F) -> T
+where
+    F: FnOnce(::std::time::Instant) -> T,
+{
+    f(::std::time::Instant::now() + timeout)
+}
 
-        assert_eq!(expected, test);
-    }
+/// Calls a closure with the deadline [`Instant`] that is a given [`Duration`] from now,
+/// returning what the closure returns.
+///
+/// Nothing is interrupted when the deadline passes: the closure decides where to check it, so
+/// long computations can stop cooperatively at points where it is safe to do so.
+///
+/// # Example
+/// ```rust
+/// use std::time::Instant;
+/// use sugars::{dur, with_timeout_poll};
+///
+/// # fn main() {
+/// let steps = with_timeout_poll!(dur!(10 milli); |deadline| {
+///     let mut steps = 0;
+///     while steps < 1_000_000 && Instant::now() < deadline {
+///         steps += 1;
+///     }
+///     steps
+/// });
+///
+/// assert!(steps > 0);
+/// # }
+/// ```
+///
+/// [`Instant`]: ::std::time::Instant
+/// [`Duration`]: ::std::time::Duration
+#[macro_export]
+macro_rules! with_timeout_poll {
+    ($timeout:expr; $f:expr $(,)?) => {
+        $crate::poll_deadline($timeout, $f)
+    };
+}
 
-    #[test]
-    fn dur_literal_sec() {
-        let expected = Duration::from_secs(10);
-        let test = dur!(10 sec);
+/// Times each closure of a list once and prints out a summary ranking them from the fastest to
+/// the slowest.
+///
+/// The closures take no arguments and must all return the same type. Evaluates to a tuple with
+/// the index of the fastest closure and a [`Vec`] with the results of all of them, in the given
+/// order.
+///
+/// # Example
+/// ```rust
+/// use sugars::{race_fastest, sleep};
+/// # fn main() {
+/// let (fastest, results) = race_fastest!([
+///     || { sleep!(20 milli); 1 },
+///     || 2,
+/// ]);
+///
+/// assert_eq!(1, fastest);
+/// assert_eq!(vec![1, 2], results);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! race_fastest {
+    ([$($f:expr),+ $(,)?]) => {{
+        let mut results = ::std::vec::Vec::new();
+        let mut timings = ::std::vec::Vec::new();
+        $(
+            let time = ::std::time::Instant::now();
+            results.push(($f)());
+            timings.push((timings.len(), stringify!($f), time.elapsed()));
+        )+
+        timings.sort_by_key(|&(_, _, elapsed)| elapsed);
+        for (rank, (i, name, elapsed)) in timings.iter().enumerate() {
+            eprintln!("{}. [{}] {} {:.6} seconds", rank + 1, i, name, elapsed.as_secs_f64());
+        }
+        (timings[0].0, results)
+    }};
+}
 
-        assert_eq!(expected, test);
-    }
+/// Build a [`Vec`] from a collection iterator comprehension, stopping once the time budget has
+/// elapsed.
+///
+/// The budget is checked before pulling each item, so an item already being produced is never
+/// interrupted, and the items produced so far are returned. It also stops when the comprehension
+/// is exhausted.
+///
+/// It also accepts the [`x in iter` shorthand](crate#shorthand-generator).
+///
+/// # Example
+/// ```rust
+/// use sugars::{ctake_for, dur};
+/// # fn main() {
+/// let w = ctake_for!(dur!(1 sec); x * 2; x in 0..5);
+/// assert_eq!(vec![0, 2, 4, 6, 8], w);
+///
+/// let z = ctake_for!(dur!(20 milli); x in (0..).inspect(|_| sugars::sleep!(5 milli)));
+/// assert!(z.len() < 10);
+/// # }
+/// ```
+///
+/// [`Vec`]: ::std::vec::Vec
+#[macro_export]
+macro_rules! ctake_for {
+    ($budget:expr; $x:ident in $($tokens: tt)+) => {
+        $crate::ctake_for!($budget; $x; $x in $($tokens)+)
+    };
 
-    #[test]
-    fn dur_identifier_sec() {
-        let expected = Duration::from_secs(10);
-        let x = 10;
-        let test = dur!(x sec);
+    ($budget:expr; $($tokens: tt)+) => {{
+        let budget: ::std::time::Duration = $budget;
+        let start = ::std::time::Instant::now();
+        let mut iter = $crate::c![$($tokens)+];
+        let mut items = ::std::vec::Vec::new();
+        while start.elapsed() < budget {
+            match ::std::iter::Iterator::next(&mut iter) {
+                Some(item) => items.push(item),
+                None => break,
+            }
+        }
+        items
+    }};
+}
 
-        assert_eq!(expected, test);
-    }
+/// Runs an expression `n` times and prints out the total and the average time per iteration
+/// in seconds.
+///
+/// The result of every run is passed through [`black_box`] so the optimizer cannot discard the
+/// work. The `warmup: w` form runs the expression `w` more times before measuring. Evaluates to
+/// the total [`Duration`] of the measured runs.
+///
+/// # Example
+/// ```rust
+/// use sugars::bench;
+/// # fn main() {
+/// let total = bench!(1000, (0..100u64).sum::<u64>());
+/// let total = bench!(1000, warmup: 100, (0..100u64).sum::<u64>());
+/// # }
+/// ```
+///
+/// [`black_box`]: ::std::hint::black_box
+/// [`Duration`]: ::std::time::Duration
+#[macro_export]
+macro_rules! bench {
+    ($n:expr, warmup: $w:expr, $e:expr $(,)?) => {{
+        for _ in 0..$w {
+            ::std::hint::black_box($e);
+        }
+        $crate::bench!($n, $e)
+    }};
+    ($n:expr, $e:expr $(,)?) => {{
+        let n: u32 = $n;
+        let time = ::std::time::Instant::now();
+        for _ in 0..n {
+            ::std::hint::black_box($e);
+        }
+        let total = time.elapsed();
+        let mean = total.checked_div(n).unwrap_or_default();
+        eprintln!(
+            "{} {} runs: {:.6} seconds total, {:.9} seconds per run",
+            stringify!($e),
+            n,
+            total.as_secs_f64(),
+            mean.as_secs_f64()
+        );
+        total
+    }};
+}
 
-    #[test]
-    fn dur_literal_nano() {
-        let expected = Duration::from_nanos(10);
-        let test = dur!(10 nano);
+/// Calls the blocker with the future, giving the closure passed to [`time_block_on`] its
+/// expected signature.
+///
+/// [`time_block_on`]: crate::time_block_on
+#[doc(hidden)]
+pub fn block_with<Fut, T, B: FnOnce(Fut) -> T>(blocker: B, fut: Fut) -> T {
+    blocker(fut)
+}
 
-        assert_eq!(expected, test);
-    }
+/// Blocks on a future with the given blocker and prints out the time it took in seconds, like
+/// [`time`].
+///
+/// The blocker is any function taking the future and returning its output, usually a closure
+/// calling the `block_on` method of an async runtime, so no runtime is tied to this crate.
+/// Evaluates to the output of the future.
+///
+/// The `to: writer` form writes the line to any [`Write`] implementor instead of stderr.
+///
+/// # Example
+/// ```rust
+/// use std::{future::Future, pin::pin, ptr, task};
+/// use sugars::time_block_on;
+///
+/// // A trivial blocker, only able to run futures that are ready on the first poll
+/// fn block_on<F: Future>(fut: F) -> F::Output {
+///     fn raw_waker() -> task::RawWaker {
+///         fn clone(_: *const ()) -> task::RawWaker {
+///             raw_waker()
+///         }
+///         fn noop(_: *const ()) {}
+///         static VTABLE: task::RawWakerVTable = task::RawWakerVTable::new(clone, noop, noop, noop);
+///         task::RawWaker::new(ptr::null(), &VTABLE)
+///     }
+///
+///     let waker = unsafe { task::Waker::from_raw(raw_waker()) };
+///     match pin!(fut).poll(&mut task::Context::from_waker(&waker)) {
+///         task::Poll::Ready(output) => output,
+///         task::Poll::Pending => panic!("the future is not ready"),
+///     }
+/// }
+///
+/// # fn main() {
+/// let x = time_block_on!(|fut| block_on(fut); async { 100 + 20 });
+/// assert_eq!(120, x);
+/// # }
+/// ```
+///
+/// [`time`]: crate::time
+/// [`Write`]: ::std::io::Write
+#[macro_export]
+macro_rules! time_block_on {
+    (to: $w:expr, $blocker:expr; $fut:expr $(,)?) => {{
+        let time = ::std::time::Instant::now();
+        match $crate::block_with($blocker, $fut) {
+            tmp => {
+                let _ = ::std::io::Write::write_fmt(
+                    &mut $w,
+                    format_args!(
+                        "{} {:.6} seconds\n",
+                        stringify!($fut),
+                        time.elapsed().as_secs_f64()
+                    ),
+                );
+                tmp
+            }
+        }
+    }};
+    ($blocker:expr; $fut:expr $(,)?) => {{
+        let time = ::std::time::Instant::now();
+        match $crate::block_with($blocker, $fut) {
+            tmp => {
+                eprintln!(
+                    "{} {:.6} seconds",
+                    stringify!($fut),
+                    time.elapsed().as_secs_f64()
+                );
+                tmp
+            }
+        }
+    }};
+}
 
-    #[test]
-    fn dur_identifier_nano() {
-        let expected = Duration::from_nanos(10);
-        let x = 10;
-        let test = dur!(x nano);
+/// Evaluate an expression only if the quiet period elapsed since the last call at the same call
+/// site, returning its value wrapped in an [`Option`].
+///
+/// Every call resets the timer, even the suppressed ones, so a burst of rapid calls only
+/// evaluates the first one (leading-edge semantics), and the next evaluation needs a call after
+/// a full quiet period. The last call time is tracked per thread.
+///
+/// Unlike [`every`], which evaluates periodically during a continuous stream of calls, this
+/// stays silent until the calls pause.
+///
+/// # Example
+/// ```rust
+/// use sugars::{debounce, dur};
+///
+/// # fn main() {
+/// let mut handled = 0;
+/// for _ in 0..100 {
+///     debounce!(dur!(200 milli); handled += 1);
+/// }
+/// assert_eq!(1, handled);
+/// # }
+/// ```
+///
+/// [`every`]: crate::every
+#[macro_export]
+macro_rules! debounce {
+    ($quiet:expr; $e:expr) => {{
+        ::std::thread_local! {
+            static LAST: ::std::cell::Cell<::std::option::Option<::std::time::Instant>> =
+                ::std::cell::Cell::new(None);
+        }
+        let quiet: ::std::time::Duration = $quiet;
+        let now = ::std::time::Instant::now();
+        let due = LAST.with(|last| match last.replace(Some(now)) {
+            Some(t) => now.duration_since(t) >= quiet,
+            None => true,
+        });
+        if due {
+            Some($e)
+        } else {
+            None
+        }
+    }};
+}
 
-        assert_eq!(expected, test);
-    }
+/// Compute the rate of events per second, given a count of events and the [`Instant`] when
+/// counting started.
+///
+/// The elapsed time is taken from `start.elapsed()`, and the rate is returned as a [`f64`].
+///
+/// # Example
+/// ```rust
+/// use std::time::{Duration, Instant};
+/// use sugars::rate;
+///
+/// # fn main() {
+/// let start = Instant::now() - Duration::from_secs(2);
+/// let r = rate!(100, start);
+/// assert!(r > 49.0 && r <= 50.0);
+/// # }
+/// ```
+///
+/// [`Instant`]: ::std::time::Instant
+#[macro_export]
+macro_rules! rate {
+    ($count:expr, $start:expr) => {
+        ($count) as f64 / $start.elapsed().as_secs_f64()
+    };
+}
+
+/// Compute the rate of events per a time unit, given a count of events, the [`Instant`] when
+/// counting started and a time pattern following the same grammar as [`dur`].
+///
+/// # Example
+/// ```rust
+/// use std::time::{Duration, Instant};
+/// use sugars::rate_per;
+///
+/// # fn main() {
+/// let start = Instant::now() - Duration::from_secs(60);
+/// let r = rate_per!(30, start, 1 min);
+/// assert!(r > 29.0 && r <= 30.0);
+/// # }
+/// ```
+///
+/// [`Instant`]: ::std::time::Instant
+/// [`dur`]: crate::dur
+#[macro_export]
+macro_rules! rate_per {
+    ($count:expr, $start:expr, $($unit:tt)+) => {
+        $crate::rate!($count, $start) * $crate::dur!($($unit)+).as_secs_f64()
+    };
+}
+
+/// Returns the baseline [`Instant`] used by [`since_start`], captured on its first call.
+///
+/// [`Instant`]: ::std::time::Instant
+/// [`since_start`]: crate::since_start
+#[doc(hidden)]
+pub fn start_instant() -> ::std::time::Instant {
+    static START: ::std::sync::OnceLock<::std::time::Instant> = ::std::sync::OnceLock::new();
+    *START.get_or_init(::std::time::Instant::now)
+}
+
+/// Returns the current [`Instant`], to be measured later with [`elapsed`].
+///
+/// # Example
+/// ```rust
+/// use sugars::{elapsed, now};
+///
+/// # fn main() {
+/// let start = now!();
+/// let millis = elapsed!(start, milli);
+/// # }
+/// ```
+///
+/// [`Instant`]: ::std::time::Instant
+/// [`elapsed`]: crate::elapsed
+#[macro_export]
+macro_rules! now {
+    () => {
+        ::std::time::Instant::now()
+    };
+}
+
+/// Returns the time elapsed since an [`Instant`].
+///
+/// With only the instant it returns a [`Duration`]. Given a time pattern unit too, it returns the
+/// elapsed time in that unit as a [`f64`].
+///
+/// **Paterns:**
+/// * week: weeks
+/// * day: days
+/// * hour: hours
+/// * min: minutes
+/// * sec: seconds
+/// * nano: nanoseconds
+/// * micro: microseconds
+/// * milli: milliseconds
+///
+/// # Example
+/// ```rust
+/// use sugars::{elapsed, now, sleep};
+///
+/// # fn main() {
+/// let start = now!();
+/// sleep!(10 milli);
+///
+/// assert!(elapsed!(start) >= std::time::Duration::from_millis(10));
+/// assert!(elapsed!(start, milli) >= 10.0);
+/// # }
+/// ```
+///
+/// [`Instant`]: ::std::time::Instant
+/// [`Duration`]: ::std::time::Duration
+#[macro_export]
+macro_rules! elapsed {
+    ($t:expr $(,)?) => {
+        ::std::time::Instant::elapsed(&$t)
+    };
+    ($t:expr, week) => {
+        $crate::elapsed!($t).as_secs_f64() / 604_800.0
+    };
+    ($t:expr, day) => {
+        $crate::elapsed!($t).as_secs_f64() / 86_400.0
+    };
+    ($t:expr, hour) => {
+        $crate::elapsed!($t).as_secs_f64() / 3_600.0
+    };
+    ($t:expr, min) => {
+        $crate::elapsed!($t).as_secs_f64() / 60.0
+    };
+    ($t:expr, sec) => {
+        $crate::elapsed!($t).as_secs_f64()
+    };
+    ($t:expr, milli) => {
+        $crate::elapsed!($t).as_secs_f64() * 1_000.0
+    };
+    ($t:expr, micro) => {
+        $crate::elapsed!($t).as_secs_f64() * 1_000_000.0
+    };
+    ($t:expr, nano) => {
+        $crate::elapsed!($t).as_secs_f64() * 1_000_000_000.0
+    };
+}
+
+/// Returns the [`Duration`] elapsed since the crate baseline instant.
+///
+/// The baseline is captured the first time this macro is used, so every later call measures
+/// from the same origin, which is useful for uptime-style logging.
+///
+/// # Example
+/// ```rust
+/// use sugars::{since_start, sleep};
+///
+/// # fn main() {
+/// let first = since_start!();
+/// sleep!(10 milli);
+/// let second = since_start!();
+///
+/// assert!(second >= first + std::time::Duration::from_millis(10));
+/// # }
+/// ```
+///
+/// [`Duration`]: ::std::time::Duration
+#[macro_export]
+macro_rules! since_start {
+    () => {
+        $crate::start_instant().elapsed()
+    };
+}
+
+/// Time a [`coarse_now`] cached instant is kept before it is replaced.
+const COARSE_RESOLUTION: ::std::time::Duration = ::std::time::Duration::from_millis(2);
+
+::std::thread_local! {
+    static COARSE: ::std::cell::Cell<(::std::time::Instant, ::std::time::Instant)> = {
+        let now = ::std::time::Instant::now();
+        ::std::cell::Cell::new((now, now + COARSE_RESOLUTION))
+    };
+}
+
+/// Returns the [`Instant`] cached for the current thread, refreshing it once its deadline has
+/// passed.
+///
+/// [`Instant`]: ::std::time::Instant
+#[doc(hidden)]
+pub fn coarse_now() -> ::std::time::Instant {
+    COARSE.with(|cache| {
+        let (cached, next_refresh) = cache.get();
+        let now = ::std::time::Instant::now();
+        if now < next_refresh {
+            return cached;
+        }
+        cache.set((now, now + COARSE_RESOLUTION));
+        now
+    })
+}
+
+/// Returns a coarse [`Instant`], refreshed at most every 2 milliseconds.
+///
+/// Each thread keeps its own cached instant with a refresh deadline. Until the deadline passes,
+/// every call returns the same instant, so all the timestamps taken within a 2 milliseconds
+/// tick compare equal, and the returned instant is never more than 2 milliseconds behind the
+/// real time, however sparse the calls are. The price is precision: it fits grouping
+/// high-frequency events by tick, but not measuring short intervals.
+///
+/// Successive calls on the same thread never go backwards.
+///
+/// # Example
+/// ```rust
+/// use sugars::coarse_now;
+///
+/// # fn main() {
+/// let first = coarse_now!();
+/// let second = coarse_now!();
+///
+/// assert!(second >= first);
+/// # }
+/// ```
+///
+/// [`Instant`]: ::std::time::Instant
+#[macro_export]
+macro_rules! coarse_now {
+    () => {
+        $crate::coarse_now()
+    };
+}
+
+/// Evaluate an expression only if at least the given interval elapsed since the last time it
+/// was evaluated at the same call site, returning its value wrapped in an [`Option`].
+///
+/// It is intended for periodic side-effects inside loops, like logging progress. The first
+/// call always evaluates the expression. The last evaluation time is tracked per thread.
+///
+/// # Example
+/// ```rust
+/// use sugars::{dur, every};
+///
+/// # fn main() {
+/// let mut runs = 0;
+/// for _ in 0..1000 {
+///     every!(dur!(5 sec) => runs += 1);
+/// }
+/// assert_eq!(1, runs);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! every {
+    ($interval:expr => $e:expr) => {{
+        ::std::thread_local! {
+            static LAST: ::std::cell::Cell<::std::option::Option<::std::time::Instant>> =
+                ::std::cell::Cell::new(None);
+        }
+        let interval: ::std::time::Duration = $interval;
+        let now = ::std::time::Instant::now();
+        let due = LAST.with(|last| match last.get() {
+            Some(t) if now.duration_since(t) < interval => false,
+            _ => {
+                last.set(Some(now));
+                true
+            }
+        });
+        if due {
+            Some($e)
+        } else {
+            None
+        }
+    }};
+}
+
+/// Returns a [`String`] with the current wall-clock time as seconds since the Unix epoch.
+///
+/// If the system clock is set before the Unix epoch, it returns `"0"`.
+///
+/// # Example
+/// ```rust
+/// use sugars::timestamp;
+///
+/// # fn main() {
+/// let ts = timestamp!();
+/// eprintln!("[{}] Starting", ts);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! timestamp {
+    () => {
+        ::std::time::SystemTime::now()
+            .duration_since(::std::time::UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs())
+            .to_string()
+    };
+}
+
+/// Returns a [`String`] with the current wall-clock time as milliseconds since the Unix epoch.
+///
+/// If the system clock is set before the Unix epoch, it returns `"0"`.
+///
+/// # Example
+/// ```rust
+/// use sugars::timestamp_millis;
+///
+/// # fn main() {
+/// let ts = timestamp_millis!();
+/// eprintln!("[{}] Starting", ts);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! timestamp_millis {
+    () => {
+        ::std::time::SystemTime::now()
+            .duration_since(::std::time::UNIX_EPOCH)
+            .map_or(0, |d| d.as_millis())
+            .to_string()
+    };
+}
+
+/// Format a [`Duration`] compactly using its largest whole unit, like `"3s"` or `"2h"`.
+///
+/// [`Duration`]: ::std::time::Duration
+#[doc(hidden)]
+pub fn humanize(d: ::std::time::Duration) -> String {
+    let secs = d.as_secs();
+    match secs {
+        0 => format!("{}ms", d.as_millis()),
+        1..=59 => format!("{}s", secs),
+        60..=3_599 => format!("{}m", secs / 60),
+        3_600..=86_399 => format!("{}h", secs / 3_600),
+        _ => format!("{}d", secs / 86_400),
+    }
+}
+
+/// Returns a [`String`] describing how long ago an [`Instant`] was, like `"3s ago"`.
+///
+/// The elapsed time is shown in its largest whole unit, which can be milliseconds (`ms`),
+/// seconds (`s`), minutes (`m`), hours (`h`) or days (`d`).
+///
+/// # Example
+/// ```rust
+/// use std::time::{Duration, Instant};
+/// use sugars::ago;
+///
+/// # fn main() {
+/// let then = Instant::now() - Duration::from_secs(150);
+/// assert_eq!("2m ago", ago!(then));
+/// # }
+/// ```
+///
+/// [`Instant`]: ::std::time::Instant
+#[macro_export]
+macro_rules! ago {
+    ($instant:expr) => {
+        format!("{} ago", $crate::humanize($instant.elapsed()))
+    };
+}
+
+/// Randomly scale a [`Duration`] into the range `[d / 2, d]`.
+///
+/// [`Duration`]: ::std::time::Duration
+#[doc(hidden)]
+pub fn jitter(d: ::std::time::Duration) -> ::std::time::Duration {
+    use ::std::{
+        collections::hash_map::RandomState,
+        hash::{BuildHasher, Hasher},
+    };
+
+    // Each `RandomState` is seeded differently, so this is random enough to spread retries.
+    let random = RandomState::new().build_hasher().finish();
+    let fraction = (random >> 11) as f64 / (1u64 << 53) as f64;
+    d.mul_f64(0.5 + fraction / 2.0)
+}
+
+/// Evaluate an expression returning a [`Result`] until it is `Ok` or the maximum number of
+/// attempts is reached, sleeping with exponential backoff and jitter between attempts.
+///
+/// The first sleep is around the `base` [`Duration`], which can be built with [`dur`], and each
+/// following sleep is multiplied by `factor`. Each sleep is randomly shortened by up to half
+/// to avoid many retrying clients to synchronize. The result of the last attempt is returned.
+///
+/// The expression is always evaluated at least once, even if `max_attempts` is zero.
+///
+/// # Example
+/// ```rust
+/// use sugars::{dur, retry_backoff};
+///
+/// # fn main() {
+/// let mut calls = 0;
+/// let res: Result<i32, &str> = retry_backoff!(5, base: dur!(1 milli), factor: 2.0; {
+///     calls += 1;
+///     if calls < 3 { Err("not yet") } else { Ok(calls) }
+/// });
+///
+/// assert_eq!(Ok(3), res);
+/// # }
+/// ```
+///
+/// [`dur`]: crate::dur
+/// [`Duration`]: ::std::time::Duration
+#[macro_export]
+macro_rules! retry_backoff {
+    ($max:expr, base: $base:expr, factor: $factor:expr; $e:expr) => {{
+        let max_attempts: usize = $max;
+        let factor: f64 = $factor;
+        let mut delay: ::std::time::Duration = $base;
+        let mut attempt = 1;
+        loop {
+            match $e {
+                Ok(value) => break Ok(value),
+                Err(err) if attempt >= max_attempts => break Err(err),
+                Err(_) => {
+                    ::std::thread::sleep($crate::jitter(delay));
+                    delay = delay.mul_f64(factor);
+                    attempt += 1;
+                }
+            }
+        }
+    }};
+}
+
+/// Apply a function to each item of a collection iterator comprehension, timing each call.
+///
+/// Returns a tuple with a [`Vec`] of the results and a [`Vec`] of the [`Duration`] each call
+/// took, in the same order.
+///
+/// It also accepts the [`x in iter` shorthand](crate#shorthand-generator).
+///
+/// # Example
+/// ```rust
+/// use sugars::measure_each;
+///
+/// # fn main() {
+/// let (results, timings) = measure_each!(|x| x * 2; x in 0..5);
+///
+/// assert_eq!(vec![0, 2, 4, 6, 8], results);
+/// assert_eq!(5, timings.len());
+/// # }
+/// ```
+///
+/// [`Duration`]: ::std::time::Duration
+#[macro_export]
+macro_rules! measure_each {
+    ($f:expr; $x:ident in $($tokens:tt)+) => {
+        $crate::measure_each!($f; $x; $x in $($tokens)+)
+    };
+
+    ($f:expr; $($tokens:tt)+) => {{
+        fn measure_each<T, R, I, F>(
+            iter: I,
+            mut f: F,
+        ) -> (::std::vec::Vec<R>, ::std::vec::Vec<::std::time::Duration>)
+        where
+            I: ::std::iter::Iterator<Item = T>,
+            F: FnMut(T) -> R,
+        {
+            let mut results = ::std::vec::Vec::with_capacity(iter.size_hint().0);
+            let mut timings = ::std::vec::Vec::with_capacity(iter.size_hint().0);
+            for item in iter {
+                let start = ::std::time::Instant::now();
+                results.push(f(item));
+                timings.push(start.elapsed());
+            }
+            (results, timings)
+        }
+        measure_each($crate::c![$($tokens)+], $f)
+    }};
+}
+
+/// Breakdown of the time spent in each phase of a [`profile_block`].
+///
+/// Phases are listed in the order they were marked with [`phase`]. The time between the last
+/// mark and the end of the block is listed as a final `"(rest)"` phase, so the phase durations
+/// always add up to the `total`.
+///
+/// [`profile_block`]: crate::profile_block
+/// [`phase`]: crate::phase
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProfileReport {
+    /// Name of the profiled block.
+    pub name: &'static str,
+    /// Name and duration of each phase, in order.
+    pub phases: Vec<(&'static str, ::std::time::Duration)>,
+    /// Time spent in the whole block.
+    pub total: ::std::time::Duration,
+}
+
+impl ::std::fmt::Display for ProfileReport {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        write!(f, "{} {:.6} seconds", self.name, self.total.as_secs_f64())?;
+        for (phase, d) in &self.phases {
+            write!(f, "\n  {} {:.6} seconds", phase, d.as_secs_f64())?;
+        }
+        Ok(())
+    }
+}
+
+struct ProfileFrame {
+    start: ::std::time::Instant,
+    marks: Vec<(&'static str, ::std::time::Instant)>,
+}
+
+::std::thread_local! {
+    static PROFILES: ::std::cell::RefCell<Vec<ProfileFrame>> = const { ::std::cell::RefCell::new(Vec::new()) };
+}
+
+/// Guard of a [`profile_block`] that prints its [`ProfileReport`] when dropped.
+///
+/// [`profile_block`]: crate::profile_block
+#[doc(hidden)]
+pub struct Profile {
+    name: &'static str,
+    finished: bool,
+}
+
+impl Profile {
+    pub fn start(name: &'static str) -> Self {
+        let frame = ProfileFrame {
+            start: ::std::time::Instant::now(),
+            marks: Vec::new(),
+        };
+        PROFILES.with(|p| p.borrow_mut().push(frame));
+        Self {
+            name,
+            finished: false,
+        }
+    }
+
+    pub fn phase(name: &'static str) {
+        let now = ::std::time::Instant::now();
+        PROFILES.with(|p| {
+            if let Some(frame) = p.borrow_mut().last_mut() {
+                frame.marks.push((name, now));
+            }
+        });
+    }
+
+    pub fn finish(mut self) -> ProfileReport {
+        self.finished = true;
+        let report = self.report();
+        eprintln!("{}", report);
+        report
+    }
+
+    fn report(&self) -> ProfileReport {
+        let end = ::std::time::Instant::now();
+        let frame = PROFILES
+            .with(|p| p.borrow_mut().pop())
+            .expect("profile frame missing");
+        let mut last = frame.start;
+        let mut phases = Vec::with_capacity(frame.marks.len() + 1);
+        for (name, at) in frame.marks.into_iter().chain(Some(("(rest)", end))) {
+            phases.push((name, at - last));
+            last = at;
+        }
+        ProfileReport {
+            name: self.name,
+            phases,
+            total: end - frame.start,
+        }
+    }
+}
+
+impl Drop for Profile {
+    fn drop(&mut self) {
+        // Still report the phases reached when the block is left by a panic.
+        if !self.finished {
+            eprintln!("{}", self.report());
+        }
+    }
+}
+
+/// Profiles a block, splitting its running time into the phases marked with [`phase`].
+///
+/// When the block ends, a breakdown of the time per phase and the total is printed to stderr.
+/// The macro evaluates to a tuple with the value of the block and its [`ProfileReport`].
+///
+/// # Example
+/// ```rust
+/// use sugars::{phase, profile_block};
+///
+/// # fn main() {
+/// let (sum, report) = profile_block!("work"; {
+///     let v: Vec<u64> = (0..1000).collect();
+///     phase!("build");
+///     let sum: u64 = v.iter().sum();
+///     phase!("sum");
+///     sum
+/// });
+///
+/// assert_eq!(499500, sum);
+/// assert_eq!("build", report.phases[0].0);
+/// assert_eq!("sum", report.phases[1].0);
+/// # }
+/// ```
+///
+/// [`phase`]: crate::phase
+/// [`ProfileReport`]: crate::ProfileReport
+#[macro_export]
+macro_rules! profile_block {
+    ($name:expr; $body:expr) => {{
+        let profile = $crate::Profile::start($name);
+        match $body {
+            tmp => (tmp, profile.finish()),
+        }
+    }};
+}
+
+/// Marks the end of a phase in the innermost [`profile_block`].
+///
+/// Does nothing when used outside of a [`profile_block`].
+///
+/// [`profile_block`]: crate::profile_block
+#[macro_export]
+macro_rules! phase {
+    ($name:expr) => {
+        $crate::Profile::phase($name)
+    };
+}
+
+/// Guard created by [`timed_scope`] that writes the time elapsed since its creation when
+/// dropped.
+///
+/// [`timed_scope`]: crate::timed_scope
+#[derive(Debug)]
+pub struct ScopeTimer<W: ::std::io::Write = ::std::io::Stderr> {
+    label: &'static str,
+    start: ::std::time::Instant,
+    out: W,
+}
+
+impl<W: ::std::io::Write> ScopeTimer<W> {
+    /// Starts a timer labeled `label` that writes to `out` when dropped.
+    pub fn new(label: &'static str, out: W) -> Self {
+        Self {
+            label,
+            start: ::std::time::Instant::now(),
+            out,
+        }
+    }
+
+    /// Returns the label of the timer.
+    pub fn label(&self) -> &'static str {
+        self.label
+    }
+
+    /// Returns the time elapsed since the timer started.
+    pub fn elapsed(&self) -> ::std::time::Duration {
+        self.start.elapsed()
+    }
+}
+
+impl<W: ::std::io::Write> Drop for ScopeTimer<W> {
+    fn drop(&mut self) {
+        let secs = self.start.elapsed().as_secs_f64();
+        let _ = writeln!(self.out, "{} {:.6} seconds", self.label, secs);
+    }
+}
+
+/// Times the enclosing scope, printing the elapsed time with a label when the scope ends.
+///
+/// The macro returns a [`ScopeTimer`] guard that has to be bound to a variable, the time is
+/// printed to stderr when it is dropped. Since it relies on [`Drop`], early returns and panics
+/// are measured too. Note that `let _ = timed_scope!(...)` drops the guard immediately.
+///
+/// # Example
+/// ```rust
+/// use sugars::timed_scope;
+///
+/// fn work() -> u64 {
+///     let _t = timed_scope!("work");
+///     (0..1000).sum()
+/// } // Prints something like `work 0.000002 seconds`
+///
+/// # fn main() {
+/// assert_eq!(499500, work());
+/// # }
+/// ```
+///
+/// **With a custom output:**
+///
+/// The line can be written to any [`Write`] implementor instead of stderr, the output is
+/// borrowed until the guard is dropped. Errors while writing are ignored.
+///
+/// ```rust
+/// use sugars::timed_scope;
+///
+/// # fn main() {
+/// let mut log = Vec::new();
+/// {
+///     let _t = timed_scope!(to: log, "block");
+/// }
+///
+/// assert!(String::from_utf8(log).unwrap().starts_with("block "));
+/// # }
+/// ```
+///
+/// [`ScopeTimer`]: crate::ScopeTimer
+/// [`Write`]: ::std::io::Write
+#[macro_export]
+macro_rules! timed_scope {
+    (to: $w:expr, $label:expr $(,)?) => {
+        $crate::ScopeTimer::new($label, &mut $w)
+    };
+    ($label:expr $(,)?) => {
+        $crate::ScopeTimer::new($label, ::std::io::stderr())
+    };
+}
+
+/// Counts events within a trailing time window, created by [`tick_counter`].
+///
+/// [`tick_counter`]: crate::tick_counter
+#[derive(Debug, Clone)]
+pub struct TickCounter {
+    window: ::std::time::Duration,
+    hits: ::std::collections::VecDeque<::std::time::Instant>,
+}
+
+impl TickCounter {
+    /// Creates a new counter over the trailing `window`.
+    pub fn new(window: ::std::time::Duration) -> Self {
+        Self {
+            window,
+            hits: ::std::collections::VecDeque::new(),
+        }
+    }
+
+    /// Records an event now.
+    pub fn hit(&mut self) {
+        let now = ::std::time::Instant::now();
+        self.evict(now);
+        self.hits.push_back(now);
+    }
+
+    /// Returns the number of events recorded within the trailing window.
+    pub fn count(&mut self) -> usize {
+        self.evict(::std::time::Instant::now());
+        self.hits.len()
+    }
+
+    /// Returns the trailing window of the counter.
+    pub fn window(&self) -> ::std::time::Duration {
+        self.window
+    }
+
+    fn evict(&mut self, now: ::std::time::Instant) {
+        while self
+            .hits
+            .front()
+            .is_some_and(|hit| now.duration_since(*hit) >= self.window)
+        {
+            self.hits.pop_front();
+        }
+    }
+}
+
+/// Create a [`TickCounter`] counting events within the given trailing window.
+///
+/// # Example
+/// ```rust
+/// use sugars::{dur, tick_counter};
+///
+/// # fn main() {
+/// let mut requests = tick_counter!(dur!(1 sec));
+/// requests.hit();
+/// requests.hit();
+///
+/// assert_eq!(2, requests.count());
+/// # }
+/// ```
+///
+/// [`TickCounter`]: crate::TickCounter
+#[macro_export]
+macro_rules! tick_counter {
+    ($window:expr $(,)?) => {
+        $crate::TickCounter::new($window)
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    #[test]
+    fn dur_literal_week() {
+        let expected = Duration::from_secs(2 * 7 * 24 * 60 * 60);
+        let test = dur!(2 week);
+
+        assert_eq!(expected, test);
+    }
+
+    #[test]
+    fn dur_identifier_week() {
+        let expected = Duration::from_secs(2 * 7 * 24 * 60 * 60);
+        let x = 2;
+        let test = dur!(x week);
+
+        assert_eq!(expected, test);
+    }
+
+    #[test]
+    fn dur_literal_day() {
+        let expected = Duration::from_secs(2 * 24 * 60 * 60);
+        let test = dur!(2 day);
+
+        assert_eq!(expected, test);
+    }
+
+    #[test]
+    fn dur_identifier_day() {
+        let expected = Duration::from_secs(2 * 24 * 60 * 60);
+        let x = 2;
+        let test = dur!(x day);
+
+        assert_eq!(expected, test);
+    }
+
+    #[test]
+    fn dur_literal_hour() {
+        let expected = Duration::from_secs(2 * 60 * 60);
+        let test = dur!(2 hour);
+
+        assert_eq!(expected, test);
+    }
+
+    #[test]
+    fn dur_identifier_hour() {
+        let expected = Duration::from_secs(2 * 60 * 60);
+        let x = 2;
+        let test = dur!(x hour);
+
+        assert_eq!(expected, test);
+    }
+
+    #[test]
+    fn dur_literal_min() {
+        let expected = Duration::from_secs(10 * 60);
+        let test = dur!(10 min);
+
+        assert_eq!(expected, test);
+    }
+
+    #[test]
+    fn dur_identifier_min() {
+        let expected = Duration::from_secs(10 * 60);
+        let x = 10;
+        let test = dur!(x min);
+
+        assert_eq!(expected, test);
+    }
+
+    #[test]
+    fn dur_literal_sec() {
+        let expected = Duration::from_secs(10);
+        let test = dur!(10 sec);
+
+        assert_eq!(expected, test);
+    }
+
+    #[test]
+    fn dur_identifier_sec() {
+        let expected = Duration::from_secs(10);
+        let x = 10;
+        let test = dur!(x sec);
+
+        assert_eq!(expected, test);
+    }
+
+    #[test]
+    fn dur_literal_nano() {
+        let expected = Duration::from_nanos(10);
+        let test = dur!(10 nano);
+
+        assert_eq!(expected, test);
+    }
+
+    #[test]
+    fn dur_identifier_nano() {
+        let expected = Duration::from_nanos(10);
+        let x = 10;
+        let test = dur!(x nano);
+
+        assert_eq!(expected, test);
+    }
 
     #[test]
     fn dur_literal_micros() {
@@ -275,4 +1555,593 @@ mod tests {
 
         assert_eq!(expected, test);
     }
+
+    #[test]
+    fn dur_expression() {
+        let n = 4;
+        assert_eq!(Duration::from_secs(5), dur!((n + 1) sec));
+        assert_eq!(Duration::from_secs(5), dur!(n + 1 sec));
+
+        let v = String::from("abc");
+        assert_eq!(Duration::from_millis(3), dur!(v.len() as u64 milli));
+        assert_eq!(
+            Duration::from_secs(3 * 60 + 8),
+            dur!(v.len() as u64 min, n * 2 sec)
+        );
+        assert_eq!(Duration::from_micros(42), dur!(u64::from(40u8) + 2 micro));
+    }
+
+    #[test]
+    fn dur_combined() {
+        assert_eq!(Duration::from_secs(90), dur!(1 min, 30 sec));
+        assert_eq!(Duration::from_millis(2_500), dur!(2 sec, 500 milli,));
+
+        let x = 3;
+        let expected = Duration::new(7 * 24 * 60 * 60 + 3 * 60, 1);
+        assert_eq!(expected, dur!(1 week, x min, 1 nano));
+    }
+
+    #[test]
+    #[should_panic(expected = "overflow when adding durations")]
+    fn dur_combined_overflow() {
+        let max = u64::MAX;
+        let _ = dur!(max sec, 1 sec);
+    }
+
+    #[test]
+    fn sleep() {
+        let start = std::time::Instant::now();
+        sleep!(1 sec);
+        assert!(start.elapsed() >= Duration::from_secs(1));
+
+        let x = 20;
+        let start = std::time::Instant::now();
+        sleep!(x milli);
+        assert!(start.elapsed() >= Duration::from_millis(20));
+
+        let start = std::time::Instant::now();
+        sleep!(x / 2 milli, 10 milli);
+        assert!(start.elapsed() >= Duration::from_millis(20));
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn sleep_async() {
+        let start = std::time::Instant::now();
+        sleep_async!(20 milli);
+        assert!(start.elapsed() >= Duration::from_millis(20));
+
+        let x = 10;
+        let start = std::time::Instant::now();
+        sleep_async!(x milli, 10 milli);
+        assert!(start.elapsed() >= Duration::from_millis(20));
+    }
+
+    #[test]
+    fn durf_literal() {
+        assert_eq!(Duration::from_secs_f64(1.5), durf!(1.5 sec));
+        assert_eq!(Duration::from_micros(500), durf!(0.5 milli));
+        assert_eq!(Duration::from_nanos(1_500), durf!(1.5 micro));
+        assert_eq!(Duration::from_secs(90), durf!(1.5 min));
+        assert_eq!(Duration::from_secs(30 * 60), durf!(0.5 hour));
+    }
+
+    #[test]
+    fn durf_expression() {
+        let x = 2.5;
+        assert_eq!(Duration::from_millis(2_500), durf!(x sec));
+        assert_eq!(Duration::from_millis(5), durf!(x * 2.0 milli));
+        assert_eq!(
+            Duration::from_secs(12 * 60 * 60),
+            durf!(f64::from(1u8) / 2.0 day)
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn durf_negative() {
+        let x = -1.0;
+        let _ = durf!(x sec);
+    }
+
+    #[test]
+    fn time_to_writer() {
+        let mut out = Vec::new();
+        let test = time!(to: out, {
+            sleep!(10 milli);
+            "done"
+        });
+        assert_eq!("done", test);
+
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains("sleep!(10 milli)"));
+        assert!(out.ends_with(" seconds\n"));
+
+        let secs: f64 = out
+            .split_whitespace()
+            .rev()
+            .nth(1)
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert!(secs >= 0.01);
+    }
+
+    #[test]
+    fn time_with() {
+        let (value, elapsed) = time_with!({
+            sleep!(50 milli);
+            "done"
+        });
+        assert_eq!("done", value);
+        assert!(elapsed >= Duration::from_millis(50));
+
+        let ((a, a_elapsed), (b, b_elapsed)) = time_with!(1 + 1, sleep!(50 milli));
+        assert_eq!(2, a);
+        assert_eq!((), b);
+        assert!(a_elapsed < b_elapsed);
+        assert!(b_elapsed >= Duration::from_millis(50));
+    }
+
+    #[test]
+    fn time_block_on() {
+        use std::{future::Future, pin::pin, ptr, task};
+
+        fn poll_once<F: Future>(fut: F) -> F::Output {
+            fn raw_waker() -> task::RawWaker {
+                fn clone(_: *const ()) -> task::RawWaker {
+                    raw_waker()
+                }
+                fn noop(_: *const ()) {}
+                static VTABLE: task::RawWakerVTable =
+                    task::RawWakerVTable::new(clone, noop, noop, noop);
+                task::RawWaker::new(ptr::null(), &VTABLE)
+            }
+
+            let waker = unsafe { task::Waker::from_raw(raw_waker()) };
+            match pin!(fut).poll(&mut task::Context::from_waker(&waker)) {
+                task::Poll::Ready(output) => output,
+                task::Poll::Pending => panic!("the future is not ready"),
+            }
+        }
+
+        let test = time_block_on!(poll_once; std::future::ready(5));
+        assert_eq!(5, test);
+
+        let mut out = Vec::new();
+        let test = time_block_on!(to: out, poll_once; async {
+            std::thread::sleep(Duration::from_millis(10));
+            "ready"
+        });
+        assert_eq!("ready", test);
+
+        let out = String::from_utf8(out).unwrap();
+        let secs: f64 = out
+            .split_whitespace()
+            .rev()
+            .nth(1)
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert!(secs > 0.0);
+    }
+
+    #[test]
+    fn bench() {
+        let mut runs = 0;
+        let total = bench!(5, {
+            runs += 1;
+            sleep!(2 milli)
+        });
+        assert_eq!(5, runs);
+        assert!(total >= Duration::from_millis(10));
+
+        let mut runs = 0;
+        let total = bench!(3, warmup: 2, {
+            runs += 1;
+            sleep!(2 milli)
+        });
+        assert_eq!(5, runs);
+        assert!(total >= Duration::from_millis(6));
+        assert!(total < Duration::from_millis(500));
+
+        assert!(bench!(0, 1 + 1) < Duration::from_millis(100));
+    }
+
+    #[test]
+    fn with_timeout_poll_finishes_early() {
+        let test = with_timeout_poll!(dur!(10 sec); |deadline| {
+            let mut sum = 0;
+            for i in 0..10 {
+                if std::time::Instant::now() > deadline {
+                    return None;
+                }
+                sum += i;
+            }
+            Some(sum)
+        });
+
+        assert_eq!(Some(45), test);
+    }
+
+    #[test]
+    fn with_timeout_poll_honors_deadline() {
+        let start = std::time::Instant::now();
+        let test = with_timeout_poll!(dur!(30 milli); |deadline| {
+            let mut steps = 0;
+            while std::time::Instant::now() <= deadline {
+                sleep!(1 milli);
+                steps += 1;
+            }
+            steps
+        });
+
+        assert!(test > 0);
+        assert!(start.elapsed() >= Duration::from_millis(30));
+        assert!(start.elapsed() < Duration::from_secs(2));
+    }
+
+    #[test]
+    fn race_fastest() {
+        let (fastest, results) = race_fastest!([
+            || {
+                sleep!(30 milli);
+                "slow"
+            },
+            || {
+                sleep!(15 milli);
+                "medium"
+            },
+            || "fast",
+        ]);
+
+        assert_eq!(2, fastest);
+        assert_eq!(vec!["slow", "medium", "fast"], results);
+
+        let (fastest, results) = race_fastest!([|| 1 + 1]);
+        assert_eq!(0, fastest);
+        assert_eq!(vec![2], results);
+    }
+
+    #[test]
+    fn timed_scope_prints_on_drop() {
+        let mut log = Vec::new();
+        {
+            let t = timed_scope!(to: log, "scope");
+            assert_eq!("scope", t.label());
+        }
+
+        let log = String::from_utf8(log).unwrap();
+        assert!(log.starts_with("scope "));
+        assert!(log.ends_with(" seconds\n"));
+        assert_eq!(1, log.lines().count());
+    }
+
+    #[test]
+    fn timed_scope_early_return() {
+        fn checked_half(log: &mut Vec<u8>, x: i32) -> Option<i32> {
+            let _t = timed_scope!(to: *log, "half");
+            if x % 2 != 0 {
+                return None;
+            }
+            Some(x / 2)
+        }
+
+        let mut log = Vec::new();
+        assert_eq!(Some(3), checked_half(&mut log, 6));
+        assert_eq!(None, checked_half(&mut log, 7));
+
+        let log = String::from_utf8(log).unwrap();
+        assert_eq!(2, log.lines().filter(|l| l.starts_with("half ")).count());
+    }
+
+    #[test]
+    fn timed_scope_panic() {
+        let mut log = Vec::new();
+        let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            let _t = timed_scope!(to: log, "panicking");
+            panic!("boom");
+        }));
+
+        assert!(res.is_err());
+        assert!(String::from_utf8(log).unwrap().starts_with("panicking "));
+    }
+
+    #[test]
+    fn ctake_for_stops_at_budget() {
+        let budget = Duration::from_millis(50);
+        let start = std::time::Instant::now();
+        let test = ctake_for!(budget; x * 10; x in (0..100).inspect(|_| sleep!(10 milli)));
+        let elapsed = start.elapsed();
+
+        assert!(!test.is_empty());
+        assert!(test.len() < 100);
+        assert!(elapsed >= budget);
+        assert!(elapsed < budget + Duration::from_millis(500));
+        let expected: Vec<_> = (0..test.len() as i32).map(|x| x * 10).collect();
+        assert_eq!(expected, test);
+    }
+
+    #[test]
+    fn ctake_for_exhausted() {
+        let test = ctake_for!(dur!(10 sec); x in 0..10, if x % 3 == 0);
+
+        assert_eq!(vec![0, 3, 6, 9], test);
+    }
+
+    #[test]
+    fn ctake_for_zero_budget() {
+        let test = ctake_for!(Duration::ZERO; x in 0..10);
+
+        assert!(test.is_empty());
+    }
+
+    #[test]
+    fn rate() {
+        use std::time::Instant;
+
+        let start = Instant::now() - Duration::from_secs(10);
+        let test = rate!(100, start);
+
+        assert!(test <= 10.0);
+        assert!(test > 9.5);
+    }
+
+    #[test]
+    fn rate_per() {
+        use std::time::Instant;
+
+        let start = Instant::now() - Duration::from_secs(10);
+        let test = rate_per!(100, start, 1 min);
+
+        assert!(test <= 600.0);
+        assert!(test > 570.0);
+
+        let test = rate_per!(100, start, 100 milli);
+        assert!(test <= 1.0);
+        assert!(test > 0.95);
+    }
+
+    #[test]
+    fn now_elapsed() {
+        let start = now!();
+        sleep!(20 milli);
+
+        let d = elapsed!(start);
+        assert!(d >= Duration::from_millis(20));
+        assert!(d < Duration::from_secs(2));
+
+        assert!(elapsed!(start, sec) >= 0.02);
+        assert!(elapsed!(start, milli) >= 20.0);
+        assert!(elapsed!(start, micro) >= 20_000.0);
+        assert!(elapsed!(start, nano) >= 20_000_000.0);
+        assert!(elapsed!(start, min) < 1.0);
+        assert!(elapsed!(start, week) < elapsed!(start, day));
+    }
+
+    #[test]
+    fn since_start() {
+        let first = since_start!();
+        sleep!(20 milli);
+        let second = since_start!();
+
+        assert!(second > first);
+        assert!(second - first >= Duration::from_millis(20));
+        assert!(second - first < Duration::from_secs(2));
+    }
+
+    #[test]
+    fn coarse_now() {
+        let first = coarse_now!();
+        let mut last = first;
+        for _ in 0..1_000 {
+            let now = coarse_now!();
+            assert!(now >= last);
+            last = now;
+        }
+
+        sleep!(20 milli);
+        assert!(coarse_now!() > first);
+    }
+
+    #[test]
+    fn coarse_now_sparse_calls() {
+        let first = coarse_now!();
+        sleep!(10 milli);
+        let second = coarse_now!();
+        sleep!(10 milli);
+        let third = coarse_now!();
+
+        assert!(second >= first + Duration::from_millis(8));
+        assert!(third >= second + Duration::from_millis(8));
+        assert!(third.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn every() {
+        fn tick(i: i32) -> Option<i32> {
+            every!(dur!(30 milli) => i)
+        }
+
+        let results: Vec<_> = (0..100).map(tick).collect();
+        assert_eq!(Some(0), results[0]);
+        assert!(results[1..].iter().all(Option::is_none));
+
+        sleep!(40 milli);
+        assert_eq!(Some(100), tick(100));
+        assert_eq!(None, tick(101));
+    }
+
+    #[test]
+    fn timestamp() {
+        // 2020-01-01T00:00:00Z
+        let test: u64 = timestamp!().parse().unwrap();
+        assert!(test > 1_577_836_800);
+    }
+
+    #[test]
+    fn timestamp_millis() {
+        let secs: u128 = timestamp!().parse().unwrap();
+        let test: u128 = timestamp_millis!().parse().unwrap();
+
+        assert!(test > 1_577_836_800_000);
+        assert!(test / 1000 >= secs);
+    }
+
+    #[test]
+    fn ago() {
+        use std::time::Instant;
+
+        let now = Instant::now();
+        assert!(ago!(now).ends_with("ms ago"));
+        assert_eq!("3s ago", ago!(now - dur!(3 sec)));
+        assert_eq!("2m ago", ago!(now - dur!(150 sec)));
+    }
+
+    #[test]
+    fn humanize() {
+        assert_eq!("250ms", crate::humanize(dur!(250 milli)));
+        assert_eq!("59s", crate::humanize(dur!(59 sec)));
+        assert_eq!("1m", crate::humanize(dur!(60 sec)));
+        assert_eq!("23h", crate::humanize(dur!(1439 min)));
+        assert_eq!("2d", crate::humanize(dur!(2880 min)));
+    }
+
+    #[test]
+    fn jitter() {
+        for _ in 0..100 {
+            let test = crate::jitter(dur!(100 milli));
+            assert!(test >= dur!(50 milli));
+            assert!(test <= dur!(100 milli));
+        }
+    }
+
+    #[test]
+    fn retry_backoff() {
+        use std::time::Instant;
+
+        let mut attempts = Vec::new();
+        let test: Result<usize, &str> = retry_backoff!(5, base: dur!(10 milli), factor: 4.0; {
+            attempts.push(Instant::now());
+            if attempts.len() < 4 {
+                Err("fail")
+            } else {
+                Ok(attempts.len())
+            }
+        });
+
+        assert_eq!(Ok(4), test);
+        let gaps: Vec<_> = attempts.windows(2).map(|w| w[1] - w[0]).collect();
+        assert!(gaps[0] >= dur!(5 milli));
+        assert!(gaps[0] < gaps[1]);
+        assert!(gaps[1] < gaps[2]);
+    }
+
+    #[test]
+    fn retry_backoff_exhausted() {
+        let mut calls = 0;
+        let test: Result<(), i32> = retry_backoff!(3, base: dur!(1 milli), factor: 2.0; {
+            calls += 1;
+            Err(calls)
+        });
+
+        assert_eq!(Err(3), test);
+    }
+
+    #[test]
+    fn retry_backoff_base() {
+        use std::time::Instant;
+
+        let start = Instant::now();
+        let test: Result<(), ()> =
+            retry_backoff!(2, base: dur!(0 sec, 20 milli), factor: 1.0; Err(()));
+        assert_eq!(Err(()), test);
+        assert!(start.elapsed() >= dur!(10 milli));
+
+        let millis = 10;
+        let start = Instant::now();
+        let test: Result<(), ()> =
+            retry_backoff!(2, base: Duration::from_millis(millis * 2), factor: 1.0; Err(()));
+        assert_eq!(Err(()), test);
+        assert!(start.elapsed() >= dur!(10 milli));
+    }
+
+    #[test]
+    fn measure_each() {
+        let (results, timings) = measure_each!(|x| {
+            if x == 2 {
+                sleep!(20 milli);
+            }
+            x * 10
+        }; x in 0..4);
+
+        assert_eq!(vec![0, 10, 20, 30], results);
+        assert_eq!(4, timings.len());
+        assert!(timings[2] >= dur!(20 milli));
+        assert_eq!(Some(&timings[2]), timings.iter().max());
+    }
+
+    #[test]
+    fn measure_each_with_conditional() {
+        let (results, timings) =
+            measure_each!(|s: String| s.len(); x.to_string(); x in 5..15, if x % 5 == 0);
+
+        assert_eq!(vec![1, 2], results);
+        assert_eq!(2, timings.len());
+    }
+
+    #[test]
+    fn debounce() {
+        fn event(i: i32) -> Option<i32> {
+            debounce!(dur!(200 milli); i)
+        }
+
+        assert_eq!(Some(0), event(0));
+        for i in 1..5 {
+            sleep!(10 milli);
+            assert_eq!(None, event(i));
+        }
+
+        sleep!(250 milli);
+        assert_eq!(Some(5), event(5));
+        assert_eq!(None, event(6));
+    }
+
+    #[test]
+    fn profile_block() {
+        let (value, report) = profile_block!("test"; {
+            sleep!(20 milli);
+            phase!("first");
+            sleep!(10 milli);
+            phase!("second");
+            7
+        });
+
+        assert_eq!(7, value);
+        assert_eq!("test", report.name);
+        assert_eq!(
+            vec!["first", "second", "(rest)"],
+            report.phases.iter().map(|p| p.0).collect::<Vec<_>>()
+        );
+        assert!(report.phases[0].1 >= dur!(20 milli));
+        assert!(report.phases[1].1 >= dur!(10 milli));
+        assert_eq!(report.total, report.phases.iter().map(|p| p.1).sum());
+    }
+
+    #[test]
+    fn tick_counter() {
+        let mut counter = tick_counter!(dur!(50 milli));
+        assert_eq!(0, counter.count());
+
+        for _ in 0..3 {
+            counter.hit();
+        }
+        assert_eq!(3, counter.count());
+
+        sleep!(60 milli);
+        assert_eq!(0, counter.count());
+
+        counter.hit();
+        assert_eq!(1, counter.count());
+    }
 }